@@ -1,5 +1,9 @@
 use std::fmt;
+use std::fs::{self, File};
 use std::process;
+use std::sync::Arc;
+
+extern crate rand;
 
 extern crate rustyline;
 use rustyline::Editor;
@@ -9,7 +13,14 @@ extern crate clap;
 use clap::{App, AppSettings, Arg, SubCommand};
 
 extern crate friday_lib;
-use friday_lib::friday::{Event, Friday, State, Using};
+use friday_lib::advisor::advise_hazard;
+use friday_lib::cards::{Card, CardId, CardSet, Handle};
+use friday_lib::discard::{plan_discard, DiscardPriorities};
+use friday_lib::friday::{Effect, Event, Friday, State};
+use friday_lib::odds::fight_odds;
+use friday_lib::prompt::Ask;
+use friday_lib::rules::RuleSet;
+use friday_lib::validate::validate;
 
 struct FmtVec<'a, T: fmt::Display>(&'a Vec<T>, usize);
 impl<'a, T: fmt::Display> fmt::Display for FmtVec<'a, T> {
@@ -24,6 +35,66 @@ impl<'a, T: fmt::Display> fmt::Display for FmtVec<'a, T> {
     }
 }
 
+/// Cards of a fight, with the handles to choose them.
+struct FmtCards<'a>(&'a [Card]);
+impl<'a> fmt::Display for FmtCards<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let map = self
+            .0
+            .iter()
+            .map(|c| format!("#{} {}", c.handle, c))
+            .collect::<Vec<_>>();
+        write!(f, "{}", map.join(", "))
+    }
+}
+
+/// Tells what an event changed, in a sentence.
+fn narrate(game: &Friday, effect: &Effect) -> String {
+    let fighting = |id: &CardId| game.card_set[*id].kind.to_fighting_card().title.clone();
+    let hazard = |id: &CardId| game.card_set[*id].kind.to_hazard_card().title.clone();
+    match effect {
+        Effect::HazardsDrawn(ids) => format!(
+            "Hazards drawn: {}",
+            ids.iter().map(hazard).collect::<Vec<_>>().join(", ")
+        ),
+        Effect::CardDrawn(id) => format!("Drew {}", fighting(id)),
+        Effect::LifeChanged { delta, reason } => {
+            format!("Life {:+} ({:?}): {}", delta, reason, game.life_points)
+        }
+        Effect::AgingAdded(id) => format!("Aging card {} added to the deck", fighting(id)),
+        Effect::DeckReshuffled => "Discard shuffled back into the deck".to_string(),
+        Effect::StepAdvanced(step) => format!("Hazards reshuffled, step {:?}", step),
+        Effect::CardDestroyed(id) => format!("Destroyed {}", fighting(id)),
+        Effect::FightWon(id) => format!("Won against {}", hazard(id)),
+        Effect::FightLost(id) => format!("Lost against {}", hazard(id)),
+        Effect::GameEnded { won } => format!("Game over, {}", if *won { "won" } else { "lost" }),
+    }
+}
+
+/// Prints what's wrong with the card set in `path`, returns the exit code.
+fn validate_cards(path: &str) -> i32 {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            return 1;
+        }
+    };
+    let diags = validate(&text);
+    for d in diags.iter() {
+        match d.line {
+            Some(line) => println!("{}:{}: {}", path, line, d.message),
+            None => println!("{}: {}", path, d.message),
+        }
+    }
+    if diags.is_empty() {
+        println!("{}: ok", path);
+        0
+    } else {
+        1
+    }
+}
+
 fn main() {
     let app = clap_app!(friday =>
        (version: crate_version!())
@@ -37,12 +108,96 @@ fn main() {
             .takes_value(true)
             .value_name("LEVEL")
             .default_value("1"),
+    )
+    .arg(
+        Arg::with_name("seed")
+            .short("s")
+            .long("seed")
+            .takes_value(true)
+            .value_name("SEED"),
+    )
+    .arg(
+        Arg::with_name("load")
+            .long("load")
+            .takes_value(true)
+            .value_name("FILE"),
+    )
+    .arg(
+        Arg::with_name("cards")
+            .long("cards")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Plays with the card set of a JSON file"),
+    )
+    .arg(
+        Arg::with_name("rules")
+            .long("rules")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Plays with the house rules of a JSON file, instead of a level"),
+    )
+    .subcommand(
+        SubCommand::with_name("cards")
+            .about("Card set tools")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                SubCommand::with_name("validate")
+                    .about("Checks that a card set file can be played")
+                    .arg(Arg::with_name("file").required(true)),
+            ),
     );
     let matches = app.get_matches();
+    if let Some(matches) = matches.subcommand_matches("cards") {
+        if let Some(matches) = matches.subcommand_matches("validate") {
+            process::exit(validate_cards(matches.value_of("file").unwrap()));
+        }
+    }
     let level = value_t!(matches, "level", usize).unwrap_or(1);
+    // where the card set comes from, for 'show log'
+    let cards = match (matches.value_of("load"), matches.value_of("cards")) {
+        (Some(path), _) => format!("saved in {}", path),
+        (None, Some(path)) => path.to_string(),
+        (None, None) => "builtin".to_string(),
+    };
 
     let mut rl = Editor::<()>::new();
-    let mut game = Friday::new(level);
+    let mut game = if let Some(path) = matches.value_of("load") {
+        match File::open(path).and_then(Friday::load) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("Failed to load {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+    } else {
+        let card_set = match matches.value_of("cards") {
+            Some(path) => match File::open(path).and_then(CardSet::from_json) {
+                Ok(cards) => Arc::new(cards),
+                Err(err) => {
+                    eprintln!("Failed to load {}: {}", path, err);
+                    process::exit(1);
+                }
+            },
+            None => CardSet::builtin(),
+        };
+        let rules = match matches.value_of("rules") {
+            Some(path) => File::open(path)
+                .and_then(RuleSet::from_json)
+                .map_err(|err| format!("Failed to load {}: {}", path, err)),
+            None => RuleSet::level(level).map_err(|err| err.to_string()),
+        };
+        let seed = value_t!(matches, "seed", u64).unwrap_or_else(|_| rand::random());
+        match rules.and_then(|rules| {
+            Friday::with_rules(rules, seed, card_set).map_err(|err| err.to_string())
+        }) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+    };
+    println!("Seed: {}", game.seed());
 
     let mut cli = App::new("friday")
         .setting(AppSettings::NoBinaryName)
@@ -52,7 +207,10 @@ fn main() {
                     .takes_value(true)
                     .possible_value("cards")
                     .possible_value("fight")
-                    .possible_value("discards"),
+                    .possible_value("discards")
+                    .possible_value("log")
+                    .possible_value("moves")
+                    .possible_value("odds"),
             ),
         )
         .subcommand(
@@ -61,6 +219,15 @@ fn main() {
                 .arg(Arg::with_name("replace").short("r").long("replace")),
         )
         .subcommand(SubCommand::with_name("use").arg(Arg::with_name("card").takes_value(true)))
+        .subcommand(SubCommand::with_name("save").arg(Arg::with_name("file").required(true)))
+        .subcommand(
+            SubCommand::with_name("play")
+                .about("Plays an event in its text form, as shown by 'show log'")
+                .arg(Arg::with_name("event").required(true).multiple(true)),
+        )
+        .subcommand(SubCommand::with_name("hint"))
+        .subcommand(SubCommand::with_name("undo"))
+        .subcommand(SubCommand::with_name("redo"))
         .subcommand(SubCommand::with_name("continue"))
         .subcommand(SubCommand::with_name("break"))
         .subcommand(SubCommand::with_name("fight"))
@@ -72,39 +239,47 @@ fn main() {
         )
         .subcommand(
             SubCommand::with_name("lose")
+                .arg(
+                    Arg::with_name("auto")
+                        .long("auto")
+                        .conflicts_with("discard")
+                        .help("Proposes the best cards to destroy, aging ones first"),
+                )
                 .arg(Arg::with_name("discard").takes_value(true).multiple(true)),
         );
 
     // game cannot be captured, as it would prevent from being used elsewhere..
-    let next = |game: &mut Friday, event: Event| {
-        if let Err(hint) = game.next(event) {
-            eprintln!("{}", hint);
+    let next = |game: &mut Friday, event: Event| match game.next(event) {
+        Ok(effects) => {
+            for effect in effects.iter() {
+                println!("{}", narrate(game, effect));
+            }
         }
+        Err(hint) => eprintln!("{}", hint),
     };
 
     loop {
-        {
-            let mut left_len = 0;
-            if let Some(left) = game.get_left() {
-                println!("left: {}", FmtVec(left, 0).to_string());
-                left_len = left.len();
-            }
-            if let Some(right) = game.get_right() {
-                println!("right: {}", FmtVec(right, left_len).to_string());
-            }
+        if let Some(left) = game.get_left() {
+            println!("left: {}", FmtCards(left));
+        }
+        if let Some(right) = game.get_right() {
+            println!("right: {}", FmtCards(right));
         }
+        let view = game.player_view();
         println!(
             "Life: {}/{}, Step: {:?}",
-            game.life_points,
-            game.max_life_points(),
-            game.step
+            view.life_points, view.max_life_points, view.step
         );
         println!(
             "# hazard:{} fighting:{} aging:{}",
-            game.hazard_deck.len(),
-            game.fighting_deck.len(),
-            game.aging_deck.len()
+            view.hazard_deck, view.fighting_deck, view.aging_deck
         );
+        if !view.fighting_top.is_empty() {
+            println!("fighting top: {:?}", view.fighting_top);
+        }
+        if !view.fighting_bottom.is_empty() {
+            println!("fighting bottom: {:?}", view.fighting_bottom);
+        }
 
         match game.state {
             State::ChooseHazard(ref h) => {
@@ -113,23 +288,16 @@ fn main() {
                     .map(|c| {
                         format!(
                             "{} ({})",
-                            c.description.kind.to_hazard_card(),
-                            c.description.kind.to_fighting_card()
+                            c.description().kind.to_hazard_card(),
+                            c.description().kind.to_fighting_card()
                         )
                     })
                     .collect();
                 println!("Choose hazard: {}", FmtVec(&descs, 0));
             }
-            State::Fighting(ref c, f, _, _, ref using) => {
+            State::Fighting(..) => {
                 println!("Fight diff: {}", game.fight_diff().unwrap());
-                println!(
-                    "Free draws left: {}",
-                    game.free_cards(&c.description.clone().kind.to_hazard_card()) - f
-                );
-                match using {
-                    Using::None => (),
-                    _ => println!("Using {:?}...", using),
-                }
+                println!("Free draws left: {}", game.free_draws_left());
             }
             State::ChoosePirate => {
                 println!("Choose pirate: {:#?}", game.pirate_cards);
@@ -146,6 +314,9 @@ fn main() {
                 println!("Unknown state: {:?}", state);
             }
         }
+        if let Some(prompt) = game.prompt() {
+            println!("> {}", prompt);
+        }
         println!();
         let readline = rl.readline(">> ");
         match readline {
@@ -153,22 +324,23 @@ fn main() {
                 if let Ok(m) = cli.get_matches_from_safe_borrow(line.split(' ')) {
                     if let Some(matches) = m.subcommand_matches("choose") {
                         if let Ok(card) = value_t!(matches.value_of("card"), usize) {
-                            match game.state {
-                                State::ChoosePirate => {
-                                    next(&mut game, Event::Choice(card));
-                                }
-                                State::ChooseHazard(_) => {
+                            let handle = Handle(card as u32);
+                            match game.prompt().map(|p| p.ask) {
+                                Some(Ask::Hazard { .. }) => {
                                     next(&mut game, Event::HazardChoice(Some(card)));
                                 }
-                                State::Fighting(_, _, _, _, Using::UnderDeck(_)) => {
+                                Some(Ask::Pirate) => {
+                                    next(&mut game, Event::PirateChoice(card));
+                                }
+                                Some(Ask::UnderDeck { .. }) => {
                                     let replace = matches.is_present("replace");
-                                    next(&mut game, Event::ChoiceUnder(card, replace));
+                                    next(&mut game, Event::ChoiceUnder(handle, replace));
                                 }
-                                State::Fighting(_, _, _, _, Using::Destroy(_))
-                                | State::Fighting(_, _, _, _, Using::Swap(_, _))
-                                | State::Fighting(_, _, _, _, Using::Double)
-                                | State::Fighting(_, _, _, _, Using::Copy) => {
-                                    next(&mut game, Event::Choice(card));
+                                Some(Ask::Destroy { .. })
+                                | Some(Ask::Swap { .. })
+                                | Some(Ask::Double)
+                                | Some(Ask::Copy) => {
+                                    next(&mut game, Event::Choice(handle));
                                 }
                                 _ => {}
                             }
@@ -176,8 +348,40 @@ fn main() {
                             next(&mut game, Event::HazardChoice(None));
                         }
                     } else if let Some(matches) = m.subcommand_matches("use") {
-                        if let Ok(card) = value_t!(matches.value_of("card"), usize) {
-                            next(&mut game, Event::Use(card));
+                        if let Ok(card) = value_t!(matches.value_of("card"), u32) {
+                            next(&mut game, Event::Use(Handle(card)));
+                        }
+                    } else if let Some(matches) = m.subcommand_matches("save") {
+                        let path = matches.value_of("file").unwrap();
+                        if let Err(err) = File::create(path).and_then(|f| game.save(f)) {
+                            eprintln!("Failed to save {}: {}", path, err);
+                        }
+                    } else if let Some(matches) = m.subcommand_matches("play") {
+                        let text: Vec<_> = matches.values_of("event").unwrap().collect();
+                        match text.join(" ").parse() {
+                            Ok(event) => next(&mut game, event),
+                            Err(err) => eprintln!("{}", err),
+                        }
+                    } else if m.subcommand_matches("hint").is_some() {
+                        let advice = advise_hazard(&game);
+                        if advice.is_empty() {
+                            println!("No hazard to choose");
+                        }
+                        for a in advice.iter() {
+                            println!("{:+.1} {}", a.value, a.explanation);
+                        }
+                    } else if m.subcommand_matches("undo").is_some() {
+                        if let Err(hint) = game.undo() {
+                            eprintln!("{}", hint);
+                        }
+                    } else if m.subcommand_matches("redo").is_some() {
+                        match game.redo() {
+                            Ok(effects) => {
+                                for effect in effects.iter() {
+                                    println!("{}", narrate(&game, effect));
+                                }
+                            }
+                            Err(hint) => eprintln!("{}", hint),
                         }
                     } else if m.subcommand_matches("continue").is_some() {
                         next(&mut game, Event::Continue);
@@ -209,13 +413,73 @@ fn main() {
                                     println!("hazard: {:?}", game.hazard_discard);
                                     println!("fighting: {:?}", game.fighting_discard);
                                 }
+                                "odds" => match fight_odds(&game) {
+                                    Some(odds) => {
+                                        println!(
+                                            "win with free draws: {:.0}%",
+                                            odds.win_free * 100.0
+                                        );
+                                        println!(
+                                            "win paying life: {:.0}%, {:.1} life on average",
+                                            odds.win * 100.0,
+                                            odds.life_cost
+                                        );
+                                    }
+                                    None => println!("No fight to draw for"),
+                                },
+                                "moves" => {
+                                    for event in game.legal_events() {
+                                        match (&event, game.discard_budget()) {
+                                            (Event::Lose(_), Some(budget)) => println!(
+                                                "{} [destroying cards worth up to {}]",
+                                                event, budget
+                                            ),
+                                            _ => println!("{}", event),
+                                        }
+                                    }
+                                }
+                                "log" => {
+                                    // all that replays the game, with the same --rules and --cards
+                                    println!("seed: {}", game.seed());
+                                    println!("rules: {}", game.rules.to_json());
+                                    println!("cards: {}", cards);
+                                    for event in game.events() {
+                                        println!("{}", event);
+                                    }
+                                }
                                 _ => {}
                             }
                         }
                     } else if let Some(matches) = m.subcommand_matches("lose") {
-                        let mut vals =
-                            values_t!(matches.values_of("discard"), usize).unwrap_or_default();
-                        next(&mut game, Event::Lose(vals.as_mut_slice()));
+                        if matches.is_present("auto") {
+                            match plan_discard(&game.player_view(), &DiscardPriorities::default()) {
+                                Some(plan) => {
+                                    let cards =
+                                        [&game.get_left().unwrap()[..], game.get_right().unwrap()]
+                                            .concat();
+                                    for c in cards.iter().filter(|c| plan.contains(&c.handle)) {
+                                        let card = c.description().kind.to_fighting_card();
+                                        println!("Destroy #{} {} ({})", c.handle, card.title, card);
+                                    }
+                                    if plan.is_empty() {
+                                        println!("Nothing to destroy");
+                                    }
+                                    let answer =
+                                        rl.readline("Lose like this? [y/N] ").unwrap_or_default();
+                                    if answer.trim().eq_ignore_ascii_case("y") {
+                                        next(&mut game, Event::Lose(plan));
+                                    }
+                                }
+                                None => println!("No fight against a hazard to lose"),
+                            }
+                        } else {
+                            let vals = values_t!(matches.values_of("discard"), u32)
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(Handle)
+                                .collect();
+                            next(&mut game, Event::Lose(vals));
+                        }
                     }
                 }
                 rl.add_history_entry(line.as_ref());
@@ -0,0 +1,96 @@
+//! Events played per second by `Friday::next()`, replaying simulated games,
+//! and the allocations they take.
+//!
+//! As a baseline, the games are replayed again cloning the `State` before
+//! each event, as `next()` did before it changed the state in place.
+//!
+//! Run with `cargo bench -p friday-lib --bench next`. To compare with another
+//! revision, copy this file into a checkout of it and run it there.
+
+extern crate friday_lib;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use friday_lib::friday::{Event, Friday};
+use friday_lib::strategy::{play, HeuristicStrategy};
+
+const GAMES: u64 = 400;
+const ROUNDS: usize = 10;
+
+/// The system allocator, counting allocations and reallocations.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Replays `games`, returning the best time of a round, in seconds, and the
+/// allocations per event.
+fn replay(games: &[(Friday, Vec<Event>)], clone_state: bool) -> (f64, f64) {
+    let events: usize = games.iter().map(|(_, log)| log.len()).sum();
+    let mut best = f64::MAX;
+    let mut allocations = 0;
+    for _ in 0..ROUNDS {
+        let mut fresh: Vec<_> = games
+            .iter()
+            .map(|(game, log)| (game.clone(), log.clone()))
+            .collect();
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for (game, log) in fresh.iter_mut() {
+            for event in log.drain(..) {
+                if clone_state {
+                    black_box(game.state.clone());
+                }
+                let _ = game.next(event);
+            }
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    }
+    (best, allocations as f64 / events as f64)
+}
+
+fn main() {
+    // the games are played beforehand, only next() is timed
+    let games: Vec<(Friday, Vec<Event>)> = (0..GAMES)
+        .map(|seed| {
+            let game = Friday::with_seed(1 + seed as usize % 4, seed).unwrap();
+            let mut played = game.clone();
+            play(&mut played, &mut HeuristicStrategy).unwrap();
+            (game, played.events().to_vec())
+        })
+        .collect();
+    let events: usize = games.iter().map(|(_, log)| log.len()).sum();
+
+    println!("{} events in {} games:", events, GAMES);
+    for (name, clone_state) in [("next()", false), ("next() cloning the State", true)].iter() {
+        let (best, allocations) = replay(&games, *clone_state);
+        println!(
+            "  {:<24} {:>9.0} events/s, {:.1} allocations/event",
+            name,
+            events as f64 / best,
+            allocations
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::friday::{Event, Friday, State};
+use crate::odds::{fight_odds, FightOdds};
+use crate::strategy::capacity_value;
+
+/// One of the ways to answer `State::ChooseHazard`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HazardAdvice {
+    pub event: Event,
+    /// Expected gain, in fighting points: the knowledge card won, less the
+    /// life lost.
+    pub value: f64,
+    /// Odds of the fight, `None` when no hazard is fought.
+    pub odds: Option<FightOdds>,
+    pub explanation: String,
+}
+
+/// Ranks the hazard choices, the best first.
+///
+/// A fight is valued as the knowledge card gained when it's won with the free
+/// draws, or the life lost when it's given up then. Returns nothing out of
+/// `State::ChooseHazard`.
+pub fn advise_hazard(game: &Friday) -> Vec<HazardAdvice> {
+    let cards = match &game.state {
+        State::ChooseHazard(cards) => cards,
+        _ => return vec![],
+    };
+
+    let mut advice = vec![];
+    for (i, card) in cards.iter().enumerate() {
+        let mut fight = game.clone();
+        if fight.next(Event::HazardChoice(Some(i))).is_err() {
+            continue;
+        }
+        let odds = match fight_odds(&fight) {
+            Some(odds) => odds,
+            None => continue,
+        };
+        let hazard = card.description().kind.to_hazard_card();
+        let knowledge = card.description().kind.to_fighting_card();
+        let gain = knowledge.fighting_value + capacity_value(&knowledge.capacity);
+        let value = odds.win_free * gain as f64 - (1.0 - odds.win_free) * odds.life_lost;
+        advice.push(HazardAdvice {
+            event: Event::HazardChoice(Some(i)),
+            value,
+            odds: Some(odds),
+            explanation: format!(
+                "#{} {}: level {} at {:?} with {} free cards, {:.0}% to win {}, else -{:.1} life",
+                i,
+                hazard.title,
+                game.hazard_level(&hazard.hazard, game.step),
+                game.step,
+                game.free_cards(hazard),
+                odds.win_free * 100.0,
+                knowledge,
+                odds.life_lost,
+            ),
+        });
+    }
+    if cards.len() == 1 {
+        advice.push(HazardAdvice {
+            event: Event::HazardChoice(None),
+            value: 0.0,
+            odds: None,
+            explanation: "skip: the hazard is discarded, nothing is won or lost".to_string(),
+        });
+    }
+
+    advice.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap());
+    advice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::FridayBuilder;
+    use crate::cards::{Capacity, CardDescription, CardId, CardKind, CardSet};
+
+    fn knowledge(hazard: &str, title: &str, capacity: Capacity) -> CardId {
+        let f = |c: &CardDescription| match &c.kind {
+            CardKind::HazardKnowledge(h, k) => {
+                h.title == hazard && k.title == title && k.capacity == capacity
+            }
+            _ => false,
+        };
+        CardSet::builtin().ids(f).next().unwrap()
+    }
+
+    #[test]
+    fn test_advise_hazard() {
+        let nutriment = knowledge("Wreck boat", "nutriment", Capacity::Life(1));
+        let weapon = knowledge("Exploring the island", "weapon", Capacity::None);
+        let cannibal = knowledge("Cannibal", "weapon", Capacity::None);
+        let game = FridayBuilder::new(1)
+            .hazard_deck(&[cannibal, nutriment, weapon])
+            .build();
+        let advice = advise_hazard(&game);
+        assert_eq!(advice.len(), 2);
+        // the wreck boat is won with any card at the green step
+        assert_eq!(advice[0].event, Event::HazardChoice(Some(0)));
+        assert_eq!(advice[0].odds.unwrap().win_free, 1.0);
+        assert!(advice[0]
+            .explanation
+            .starts_with("#0 Wreck boat: level 0 at Green"));
+        assert!(advice[0].value > advice[1].value);
+
+        let game = FridayBuilder::new(1).hazard_deck(&[cannibal]).build();
+        let advice = advise_hazard(&game);
+        assert_eq!(advice.len(), 2);
+        assert_eq!(advice[0].event, Event::HazardChoice(None));
+    }
+}
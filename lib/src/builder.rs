@@ -0,0 +1,837 @@
+use std::sync::Arc;
+
+use crate::cards::{Card, CardId, CardSet, Step};
+use crate::friday::Friday;
+use crate::rules::RuleSet;
+
+/// Sets up a game in a given position, to try out a scenario.
+///
+/// Anything left unset is dealt from the seed, as by `Friday::with_seed()`.
+/// Decks are given bottom to top, like the `Friday` fields: the last card is
+/// drawn first.
+#[derive(Debug, Clone)]
+pub struct FridayBuilder {
+    rules: RuleSet,
+    seed: u64,
+    card_set: Arc<CardSet>,
+    life_points: Option<isize>,
+    step: Option<Step>,
+    aging_deck: Option<Vec<CardId>>,
+    fighting_deck: Option<Vec<CardId>>,
+    fighting_discard: Option<Vec<CardId>>,
+    hazard_deck: Option<Vec<CardId>>,
+    hazard_discard: Option<Vec<CardId>>,
+    pirate_cards: Option<Vec<CardId>>,
+    fight: Option<CardId>,
+}
+
+fn cards(set: &CardSet, ids: &[CardId]) -> Vec<Card> {
+    ids.iter().map(|id| set.card(*id)).collect()
+}
+
+impl FridayBuilder {
+    /// Sets up a game at `level`, from 1 to 4.
+    ///
+    /// Panics if the level is out of range, see `RuleSet::level()`.
+    pub fn new(level: usize) -> Self {
+        Self {
+            rules: RuleSet::level(level).unwrap(),
+            seed: 0,
+            card_set: CardSet::builtin(),
+            life_points: None,
+            step: None,
+            aging_deck: None,
+            fighting_deck: None,
+            fighting_discard: None,
+            hazard_deck: None,
+            hazard_discard: None,
+            pirate_cards: None,
+            fight: None,
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Deals the game with house rules, validated by `build()`.
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn card_set(mut self, card_set: Arc<CardSet>) -> Self {
+        self.card_set = card_set;
+        self
+    }
+
+    pub fn life_points(mut self, life_points: isize) -> Self {
+        self.life_points = Some(life_points);
+        self
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn aging_deck(mut self, ids: &[CardId]) -> Self {
+        self.aging_deck = Some(ids.to_vec());
+        self
+    }
+
+    pub fn fighting_deck(mut self, ids: &[CardId]) -> Self {
+        self.fighting_deck = Some(ids.to_vec());
+        self
+    }
+
+    pub fn fighting_discard(mut self, ids: &[CardId]) -> Self {
+        self.fighting_discard = Some(ids.to_vec());
+        self
+    }
+
+    pub fn hazard_deck(mut self, ids: &[CardId]) -> Self {
+        self.hazard_deck = Some(ids.to_vec());
+        self
+    }
+
+    pub fn hazard_discard(mut self, ids: &[CardId]) -> Self {
+        self.hazard_discard = Some(ids.to_vec());
+        self
+    }
+
+    pub fn pirate_cards(mut self, ids: &[CardId]) -> Self {
+        self.pirate_cards = Some(ids.to_vec());
+        self
+    }
+
+    /// Starts the game fighting `hazard`, instead of choosing among the
+    /// first hazard cards.
+    pub fn fight(mut self, hazard: CardId) -> Self {
+        self.fight = Some(hazard);
+        self
+    }
+
+    /// Panics if the rules can't deal a game with the card set.
+    pub fn build(self) -> Friday {
+        let set = self.card_set.clone();
+        self.rules.validate(&set).unwrap();
+        let mut game = Friday::deal(self.rules, self.seed, self.card_set);
+        if let Some(life_points) = self.life_points {
+            game.life_points = life_points;
+        }
+        if let Some(step) = self.step {
+            game.step = step;
+        }
+        if let Some(ids) = self.aging_deck {
+            game.aging_deck = cards(&set, &ids);
+        }
+        if let Some(ids) = self.fighting_deck {
+            game.fighting_deck = cards(&set, &ids);
+        }
+        if let Some(ids) = self.fighting_discard {
+            game.fighting_discard = cards(&set, &ids);
+        }
+        if let Some(ids) = self.hazard_deck {
+            game.hazard_deck = cards(&set, &ids);
+        }
+        if let Some(ids) = self.hazard_discard {
+            game.hazard_discard = cards(&set, &ids);
+        }
+        if let Some(ids) = self.pirate_cards {
+            game.pirate_cards = cards(&set, &ids);
+        }
+        game.begin(self.fight.map(|id| set.card(id)));
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter;
+
+    use crate::cards::{Capacity, CardDescription, CardKind, Handle, Hazard};
+    use crate::error::GameError;
+    use crate::friday::{Effect, Event, LifeReason, State, Using};
+
+    fn find<F: Fn(&CardDescription) -> bool>(f: F) -> CardId {
+        CardSet::builtin().ids(f).next().expect("no such card")
+    }
+
+    fn starting(title: &str) -> CardId {
+        find(|c| c.is_starting() && c.kind.to_fighting_card().title == title)
+    }
+
+    fn aging(title: &str) -> CardId {
+        find(|c| {
+            (c.is_aging_normal() || c.is_aging_difficult())
+                && c.kind.to_fighting_card().title == title
+        })
+    }
+
+    /// The hazard card whose knowledge side has `capacity`, fighting `hazard`.
+    fn knowledge(hazard: &str, capacity: Capacity) -> CardId {
+        find(|c| match &c.kind {
+            CardKind::HazardKnowledge(h, k) => h.title == hazard && k.capacity == capacity,
+            _ => false,
+        })
+    }
+
+    fn pirate<F: Fn(&Hazard) -> bool>(f: F) -> CardId {
+        find(|c| c.is_pirate() && f(&c.kind.to_hazard_card().hazard))
+    }
+
+    fn wreck() -> CardId {
+        knowledge("Wreck boat", Capacity::Card(2))
+    }
+
+    fn weak() -> CardId {
+        starting("weak")
+    }
+
+    fn genius() -> CardId {
+        starting("genius")
+    }
+
+    fn focused() -> CardId {
+        starting("focused")
+    }
+
+    fn cards_in_fight(game: &Friday) -> Vec<(CardId, bool)> {
+        let left = game.get_left().unwrap();
+        let right = game.get_right().unwrap();
+        left.iter()
+            .chain(right.iter())
+            .map(|c| (c.id, c.destroy))
+            .collect()
+    }
+
+    /// The card at `i` in the fight, left pile first.
+    fn nth(game: &Friday, i: usize) -> Handle {
+        let left = game.get_left().unwrap();
+        let right = game.get_right().unwrap();
+        left.iter().chain(right.iter()).nth(i).unwrap().handle
+    }
+
+    fn fight(game: &mut Friday, n: usize) {
+        for _ in 0..n {
+            game.next(Event::Fight).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let game = FridayBuilder::new(1)
+            .life_points(7)
+            .step(Step::Yellow)
+            .fighting_deck(&[weak(), genius()])
+            .hazard_deck(&[wreck(), wreck(), wreck()])
+            .build();
+        assert_eq!(game.life_points, 7);
+        assert_eq!(game.step, Step::Yellow);
+        assert_eq!(game.fighting_deck.len(), 2);
+        assert_eq!(game.hazard_deck.len(), 1);
+        match &game.state {
+            State::ChooseHazard(h) => assert_eq!(h.len(), 2),
+            s => panic!("unexpected state {:?}", s),
+        }
+
+        // undo goes back to the built game, not to a dealt one
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), genius()])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.undo().unwrap();
+        assert_eq!(game.fighting_deck.len(), 2);
+        fight(&mut game, 1);
+        assert_eq!(cards_in_fight(&game), vec![(genius(), false)]);
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut game = FridayBuilder::new(1)
+            .life_points(10)
+            .step(Step::Red)
+            .fighting_deck(&[weak()])
+            .aging_deck(&[aging("stupid")])
+            .fight(wreck())
+            .build();
+        assert_eq!(game.next(Event::Fight), Ok(vec![Effect::CardDrawn(weak())]));
+        // the deck is empty, the aging card is shuffled in
+        assert_eq!(
+            game.next(Event::Fight),
+            Ok(vec![
+                Effect::LifeChanged {
+                    delta: -1,
+                    reason: LifeReason::DrawCost
+                },
+                Effect::AgingAdded(aging("stupid")),
+                Effect::DeckReshuffled,
+                Effect::CardDrawn(aging("stupid")),
+            ])
+        );
+        let effects = game.next(Event::Lose(vec![])).unwrap();
+        assert_eq!(
+            effects[..2],
+            [
+                Effect::FightLost(wreck()),
+                Effect::LifeChanged {
+                    delta: -5,
+                    reason: LifeReason::FightLost
+                },
+            ]
+        );
+        match effects[2] {
+            Effect::HazardsDrawn(ref cards) => assert_eq!(cards.len(), 2),
+            ref e => panic!("unexpected effect {:?}", e),
+        }
+
+        // the event that ends the game still tells what it changed
+        let mut game = FridayBuilder::new(1)
+            .life_points(2)
+            .step(Step::Red)
+            .fighting_deck(&[weak()])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(
+            game.next(Event::Lose(vec![])),
+            Ok(vec![
+                Effect::FightLost(wreck()),
+                Effect::LifeChanged {
+                    delta: -2,
+                    reason: LifeReason::FightLost
+                },
+                Effect::GameEnded { won: false },
+            ])
+        );
+        assert!(game.is_ended());
+        assert_eq!(game.events().last(), Some(&Event::Lose(vec![])));
+    }
+
+    #[test]
+    fn test_win_and_lose() {
+        // step Green: the wreck is 0, and a free card is enough
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), weak()])
+            .fight(wreck())
+            .build();
+        assert_eq!(game.next(Event::Win), Err(GameError::MustDrawFirst));
+        fight(&mut game, 1);
+        game.next(Event::Win).unwrap();
+        assert_eq!(game.fighting_discard.len(), 2);
+        assert!(game.fighting_discard.iter().any(|c| c.id == wreck()));
+
+        // step Red: the wreck is 3, the loss costs 3 life and a card per point
+        let mut game = FridayBuilder::new(1)
+            .life_points(10)
+            .step(Step::Red)
+            .fighting_deck(&[weak(), weak(), weak()])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(game.fight_diff(), Some(-3));
+        // the 2nd card costs a life point
+        assert_eq!(game.draw_cost(), 1);
+        fight(&mut game, 1);
+        assert_eq!(game.life_points, 9);
+        game.next(Event::Lose(vec![nth(&game, 0), nth(&game, 1)]))
+            .unwrap();
+        assert_eq!(game.life_points, 6);
+        assert_eq!(game.destroyed.len(), 2);
+        assert_eq!(game.hazard_discard.last().unwrap().id, wreck());
+
+        // aging cards cost 2 to destroy
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Yellow)
+            .fighting_deck(&[aging("very tired")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(
+            game.next(Event::Lose(vec![nth(&game, 0)])),
+            Err(GameError::DiscardTooCostly { cost: 2, diff: -1 })
+        );
+
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Red)
+            .fighting_deck(&[aging("stupid")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(game.fight_diff(), Some(-5));
+        game.next(Event::Lose(vec![nth(&game, 0)])).unwrap();
+        assert_eq!(game.destroyed[0].id, aging("stupid"));
+    }
+
+    #[test]
+    fn test_died_ending_fight() {
+        // "hungry" costs the last life point once the fight is won
+        let mut game = FridayBuilder::new(1)
+            .life_points(1)
+            .fighting_deck(&[aging("hungry"), weak()])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 2);
+        let effects = game.next(Event::Win).unwrap();
+        assert_eq!(effects.last(), Some(&Effect::GameEnded { won: false }));
+        assert!(game.is_ended());
+        // each card is discarded once
+        let discard: Vec<_> = game.fighting_discard.iter().map(|c| c.id).collect();
+        assert_eq!(discard, vec![wreck(), weak(), aging("hungry")]);
+    }
+
+    #[test]
+    fn test_life_and_cards() {
+        let mut game = FridayBuilder::new(1)
+            .life_points(21)
+            .fighting_deck(&[weak(), weak(), weak(), starting("eating")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        // life is capped
+        assert_eq!(game.life_points, 22);
+        assert_eq!(
+            game.next(Event::Use(nth(&game, 0))),
+            Err(GameError::CardAlreadyTapped(nth(&game, 0)))
+        );
+
+        // +2 cards are free, and drawn one after the other
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[
+                weak(),
+                weak(),
+                weak(),
+                knowledge("Wreck boat", Capacity::Card(2)),
+            ])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        assert_eq!(game.get_right().unwrap().len(), 1);
+        game.next(Event::Continue).unwrap();
+        assert_eq!(game.get_right().unwrap().len(), 2);
+        assert_eq!(game.life_points, 20);
+        assert_eq!(
+            game.next(Event::Continue),
+            Err(GameError::UnexpectedEvent(Event::Continue))
+        );
+    }
+
+    #[test]
+    fn test_destroy() {
+        let knowledge = knowledge("Wild animals", Capacity::Destroy);
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(), aging("stupid"), knowledge])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Fight).unwrap();
+        assert_eq!(game.fight_diff(), Some(1));
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        assert_eq!(
+            game.next(Event::Choice(nth(&game, 0))),
+            Err(GameError::SelfTarget(nth(&game, 0)))
+        );
+        game.next(Event::Choice(nth(&game, 1))).unwrap();
+        // a destroyed card doesn't count anymore, and leaves the game
+        assert_eq!(game.fight_diff(), Some(3));
+        game.next(Event::Win).unwrap();
+        assert_eq!(game.destroyed.len(), 1);
+        assert_eq!(game.destroyed[0].id, aging("stupid"));
+    }
+
+    #[test]
+    fn test_double() {
+        let repetition = knowledge("Exploring the island", Capacity::Double);
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Yellow)
+            .fighting_deck(&[genius(), repetition])
+            .fight(knowledge("Exploring the island", Capacity::Double))
+            .build();
+        fight(&mut game, 2);
+        assert_eq!(game.fight_diff(), Some(0));
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        game.next(Event::Choice(nth(&game, 1))).unwrap();
+        assert_eq!(game.fight_diff(), Some(2));
+    }
+
+    #[test]
+    fn test_copy() {
+        let mimicry = knowledge("Wreck boat", Capacity::Copy);
+        let destroy = knowledge("Wreck boat", Capacity::Destroy);
+        let swap = knowledge("Wreck boat", Capacity::Swap(2));
+
+        // copy of destroy, which has been used already
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Red)
+            .fighting_deck(&[aging("stupid"), aging("stupid"), destroy, mimicry])
+            .fight(knowledge("Exploring the island", Capacity::Double))
+            .build();
+        fight(&mut game, 4);
+        assert_eq!(game.fight_diff(), Some(-10));
+        game.next(Event::Use(nth(&game, 1))).unwrap();
+        game.next(Event::Choice(nth(&game, 2))).unwrap();
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        game.next(Event::Choice(nth(&game, 1))).unwrap();
+        assert_eq!(
+            game.next(Event::Choice(nth(&game, 1))),
+            Err(GameError::SelfTarget(nth(&game, 1)))
+        );
+        game.next(Event::Choice(nth(&game, 3))).unwrap();
+        assert_eq!(game.fight_diff(), Some(-6));
+        // the copy works like the copied card, once
+        assert_eq!(
+            game.next(Event::Use(nth(&game, 0))),
+            Err(GameError::CardAlreadyTapped(nth(&game, 0)))
+        );
+        let destroyed: Vec<_> = cards_in_fight(&game).iter().map(|c| c.1).collect();
+        assert_eq!(destroyed, vec![false, false, true, true]);
+
+        // copy of a 2× exchange
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(), genius(), weak(), swap, mimicry])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        fight(&mut game, 2);
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        game.next(Event::Choice(nth(&game, 1))).unwrap();
+        assert_eq!(
+            game.next(Event::Choice(nth(&game, 1))),
+            Err(GameError::SelfTarget(nth(&game, 1)))
+        );
+        game.next(Event::Choice(nth(&game, 2))).unwrap();
+        game.next(Event::Choice(nth(&game, 0))).unwrap();
+        assert_eq!(game.fight_diff(), Some(4));
+        assert_eq!(game.fighting_discard.len(), 2);
+        assert!(game.fighting_deck.is_empty());
+    }
+
+    #[test]
+    fn test_lower_step() {
+        let reader = knowledge("Wreck boat", Capacity::LowerStep);
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Red)
+            .fighting_deck(&[weak(), reader])
+            .fight(knowledge("Cannibal", Capacity::None))
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(game.fight_diff(), Some(-14));
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        assert_eq!(game.fight_diff(), Some(-9));
+        game.next(Event::Lose(vec![])).unwrap();
+        // only for this fight
+        assert_eq!(game.step_modif, 0);
+    }
+
+    #[test]
+    fn test_sort() {
+        let vision = knowledge("Wild animals", Capacity::Sort);
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), genius(), aging("stupid"), focused(), vision])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        game.next(Event::Continue).unwrap();
+        game.next(Event::Continue).unwrap();
+        assert_eq!(game.next(Event::Continue), Err(GameError::SortFull));
+        // the cards drawn can't be left aside
+        let cards = game.fighting_deck.len();
+        for event in [Event::Fight, Event::Break, Event::Win, Event::Lose(vec![])].iter() {
+            assert_eq!(
+                game.next(event.clone()),
+                Err(GameError::UnexpectedEvent(event.clone()))
+            );
+        }
+        assert_eq!(game.fighting_deck.len(), cards);
+        assert_eq!(
+            game.next(Event::Sort(vec![0, 0, 1], false)),
+            Err(GameError::InvalidSort(vec![0, 0, 1]))
+        );
+        // drawn: focused, stupid, genius; stupid is destroyed, genius on top
+        game.next(Event::Sort(vec![1, 0, 2], true)).unwrap();
+        assert_eq!(game.destroyed[0].id, aging("stupid"));
+        let deck: Vec<_> = game.fighting_deck.iter().map(|c| c.id).collect();
+        assert_eq!(deck, vec![weak(), focused(), genius()]);
+        let top: Vec<_> = game
+            .player_view()
+            .fighting_top
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(top, vec![focused(), genius()]);
+        match &game.state {
+            State::Fighting(_, _, _, _, Using::None) => {}
+            s => panic!("unexpected state {:?}", s),
+        }
+    }
+
+    #[test]
+    fn test_under_deck() {
+        let trick = knowledge("Wreck boat", Capacity::UnderDeck);
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), genius(), aging("stupid"), trick])
+            .fight(knowledge("Exploring the island", Capacity::Double))
+            .build();
+        fight(&mut game, 2);
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        assert_eq!(
+            game.next(Event::ChoiceUnder(nth(&game, 0), false)),
+            Err(GameError::SelfTarget(nth(&game, 0)))
+        );
+        // the stupid card goes below the stack, the genius replaces it
+        game.next(Event::ChoiceUnder(nth(&game, 1), true)).unwrap();
+        assert_eq!(
+            cards_in_fight(&game),
+            vec![(trick, false), (genius(), false)]
+        );
+        assert_eq!(game.fighting_deck[0].id, aging("stupid"));
+        assert_eq!(game.fighting_deck.len(), 2);
+        let view = game.player_view();
+        assert_eq!(view.fighting_bottom.len(), 1);
+        assert_eq!(view.fighting_bottom[0].id, aging("stupid"));
+
+        // cards drawn for a life point can't be replaced
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), trick, genius()])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 3);
+        game.next(Event::Use(nth(&game, 1))).unwrap();
+        assert_eq!(
+            game.next(Event::ChoiceUnder(nth(&game, 2), true)),
+            Err(GameError::CannotReplace(nth(&game, 2)))
+        );
+    }
+
+    #[test]
+    fn test_handles() {
+        let trick = knowledge("Wreck boat", Capacity::UnderDeck);
+        let destroy = knowledge("Wild animals", Capacity::Destroy);
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(), destroy, aging("stupid"), trick])
+            .fight(knowledge("Exploring the island", Capacity::Double))
+            .build();
+        fight(&mut game, 2);
+        game.next(Event::Use(Handle(1))).unwrap();
+        game.next(Event::ChoiceUnder(Handle(2), false)).unwrap();
+        // drawn again, the card would get a new handle
+        assert_eq!(game.fighting_deck[0].id, aging("stupid"));
+        fight(&mut game, 1);
+        assert_eq!(nth(&game, 1), Handle(3));
+
+        game.next(Event::Use(Handle(3))).unwrap();
+        assert_eq!(
+            game.next(Event::Choice(Handle(2))),
+            Err(GameError::UnknownCard(Handle(2)))
+        );
+        game.next(Event::Choice(Handle(1))).unwrap();
+        assert_eq!(cards_in_fight(&game), vec![(trick, true), (destroy, false)]);
+    }
+
+    #[test]
+    fn test_aging() {
+        // Stop: no more free cards
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[weak(), aging("very tired")])
+            .fight(knowledge("Cannibal", Capacity::None))
+            .build();
+        assert_eq!(game.free_draws_left(), 5);
+        fight(&mut game, 1);
+        assert_eq!(game.free_draws_left(), 0);
+        fight(&mut game, 1);
+        assert_eq!(game.life_points, 19);
+
+        // the highest card counts 0
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(), focused(), aging("afraid")])
+            .fight(knowledge("Cannibal", Capacity::None))
+            .build();
+        fight(&mut game, 3);
+        assert_eq!(game.fight_diff(), Some(1 - 5));
+
+        // life lost at the end of the fight, unless destroyed
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(), aging("very hungry")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Win).unwrap();
+        assert_eq!(game.life_points, 18);
+
+        let mut game = FridayBuilder::new(1)
+            .step(Step::Red)
+            .fighting_deck(&[aging("very hungry")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        game.next(Event::Lose(vec![nth(&game, 0)])).unwrap();
+        assert_eq!(game.life_points, 17);
+    }
+
+    #[test]
+    fn test_empty_deck() {
+        // an empty stack gets the discard back, with a new aging card
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[])
+            .fighting_discard(&[weak(), weak()])
+            .aging_deck(&[aging("idiot"), aging("stupid")])
+            .fight(wreck())
+            .build();
+        fight(&mut game, 1);
+        assert_eq!(game.aging_deck.len(), 1);
+        assert_eq!(game.fighting_deck.len(), 2);
+        assert!(game.fighting_discard.is_empty());
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut game = FridayBuilder::new(1)
+            .fighting_deck(&[genius(); 6])
+            .hazard_deck(&[wreck(), wreck()])
+            .pirate_cards(&[pirate(|h| *h == Hazard::Pirate(20))])
+            .build();
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        fight(&mut game, 1);
+        game.next(Event::Win).unwrap();
+        // the hazard stack is empty: the discarded hazards are back, a step higher
+        assert_eq!(game.step, Step::Yellow);
+        assert!(game.hazard_deck.is_empty());
+        assert!(game.hazard_discard.is_empty());
+
+        // the last hazard card alone can be skipped
+        game.next(Event::HazardChoice(None)).unwrap();
+        assert_eq!(game.step, Step::Red);
+        match &game.state {
+            State::ChooseHazard(h) => assert_eq!(h.len(), 1),
+            s => panic!("unexpected state {:?}", s),
+        }
+
+        // one pirate only: it's fought directly
+        game.next(Event::HazardChoice(None)).unwrap();
+        assert_eq!(game.step, Step::Pirate);
+        match &game.state {
+            State::Fighting(c, _, _, _, _) => assert!(c.description().is_pirate()),
+            s => panic!("unexpected state {:?}", s),
+        }
+    }
+
+    fn pirate_fight(hazard: CardId, deck: &[CardId]) -> Friday {
+        FridayBuilder::new(1)
+            .step(Step::Pirate)
+            .fighting_deck(deck)
+            .pirate_cards(&[])
+            .fight(hazard)
+            .build()
+    }
+
+    #[test]
+    fn test_pirates() {
+        let p = pirate(|h| *h == Hazard::Pirate(20));
+        let mut game = pirate_fight(p, &[genius(); 20]);
+        assert_eq!(game.free_draws_left(), 6);
+        fight(&mut game, 6);
+        assert_eq!(game.fight_diff(), Some(-8));
+        assert_eq!(
+            game.next(Event::Lose(vec![])),
+            Err(GameError::CannotLoseToPirate)
+        );
+        fight(&mut game, 4);
+        assert_eq!(game.life_points, 16);
+        game.next(Event::Win).unwrap();
+        // all pirates beaten: the game is won
+        assert_eq!(game.pirate_cards_won.len(), 1);
+        match game.state {
+            State::Ended(true) => {}
+            ref s => panic!("unexpected state {:?}", s),
+        }
+    }
+
+    #[test]
+    fn test_pirate_twice_life() {
+        let p = pirate(|h| *h == Hazard::PirateTwiceLife(16));
+        let mut game = pirate_fight(p, &[genius(); 9]);
+        fight(&mut game, 7);
+        assert_eq!(game.draw_cost(), 2);
+        fight(&mut game, 1);
+        assert_eq!(game.life_points, 18);
+    }
+
+    #[test]
+    fn test_pirate_aging() {
+        let p = pirate(|h| *h == Hazard::PirateAging);
+        let mut game = pirate_fight(p, &[genius(); 5]);
+        // two aging cards added out of 10 at level 1
+        game.aging_deck.truncate(8);
+        assert_eq!(game.free_draws_left(), 5);
+        fight(&mut game, 5);
+        assert_eq!(game.fight_diff(), Some(6));
+
+        // more aging cards than dealt don't make it negative
+        let extra = game.aging_deck[0].clone();
+        game.aging_deck.extend(iter::repeat_n(extra, 5));
+        assert_eq!(game.fight_diff(), Some(10));
+    }
+
+    #[test]
+    fn test_pirate_half() {
+        let p = pirate(|h| *h == Hazard::PirateHalf(22));
+        let deck = [
+            genius(),
+            genius(),
+            genius(),
+            weak(),
+            aging("stupid"),
+            genius(),
+            genius(),
+            genius(),
+            genius(),
+        ];
+        let mut game = pirate_fight(p, &deck);
+        fight(&mut game, 9);
+        // 5 cards out of 9 count: stupid first, then 4 geniuses
+        assert_eq!(game.fight_diff(), Some(-2 + 8 - 22));
+
+        let mut game = pirate_fight(p, &[genius(), genius(), aging("afraid"), weak()]);
+        fight(&mut game, 4);
+        // afraid and a genius count, and afraid turns the genius to 0
+        assert_eq!(game.fight_diff(), Some(-22));
+    }
+
+    #[test]
+    fn test_pirate_hazard() {
+        let p = pirate(|h| *h == Hazard::PirateHazard);
+        let mut game = pirate_fight(p, &[genius(); 10]);
+        game.hazard_deck = cards(
+            &game.card_set,
+            &[wreck(), knowledge("Cannibal", Capacity::None)],
+        );
+        game.hazard_discard = cards(
+            &game.card_set,
+            &[knowledge("Exploring the island", Capacity::Double)],
+        );
+        // free cards and levels (at Red) of all remaining hazards
+        assert_eq!(game.free_draws_left(), 1 + 5 + 2);
+        fight(&mut game, 8);
+        assert_eq!(game.fight_diff(), Some(16 - 3 - 14 - 6));
+    }
+
+    #[test]
+    fn test_pirate_add() {
+        let p = pirate(|h| *h == Hazard::PirateAdd(52));
+        let destroy = knowledge("Wild animals", Capacity::Destroy);
+        let mut game = pirate_fight(p, &[weak(), weak(), weak(), destroy]);
+        fight(&mut game, 4);
+        assert_eq!(game.fight_diff(), Some(3 + 4 - 52));
+        game.next(Event::Use(nth(&game, 0))).unwrap();
+        game.next(Event::Choice(nth(&game, 3))).unwrap();
+        // a destroyed card doesn't count at all
+        assert_eq!(game.fight_diff(), Some(3 + 3 - 52));
+    }
+}
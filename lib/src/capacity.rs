@@ -0,0 +1,321 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::cards::{Capacity, Card, CardSet, Handle};
+use crate::error::GameError;
+use crate::friday::{Friday, LifeReason, Using};
+
+/// Behavior of a knowledge card capacity.
+///
+/// Every hook does nothing by default.
+pub trait CapacityEffect: Send + Sync {
+    /// Whether the card must be used to trigger its capacity.
+    fn is_active(&self) -> bool {
+        false
+    }
+
+    /// The card is used, or copied.
+    fn on_use(&self, _ctx: &mut UseContext) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    /// Changes the values of the cards that count in the fight, sorted from
+    /// the lowest.
+    fn on_fight_value(&self, _values: &mut Vec<isize>) {}
+
+    /// Changes the number of free cards left to draw, once the card is drawn
+    /// for free.
+    fn on_draw(&self, free_draws_left: u8) -> u8 {
+        free_draws_left
+    }
+
+    /// The fight is over, and the card goes to the discard pile.
+    fn on_end_fight(&self, _game: &mut Friday) -> Result<(), GameError> {
+        Ok(())
+    }
+}
+
+/// The fight in which a capacity is used.
+pub struct UseContext<'a> {
+    pub game: &'a mut Friday,
+    /// The used card.
+    pub card: Handle,
+    pub using: &'a mut Using,
+    pub left: &'a mut Vec<Card>,
+    pub right: &'a mut Vec<Card>,
+}
+
+impl<'a> UseContext<'a> {
+    /// Draws `n` cards for free, one now and the others with `Continue`.
+    pub fn draw(&mut self, n: i8) -> Result<(), GameError> {
+        self.game.use_draw_card(self.using, n, self.right)
+    }
+
+    /// Draws the first card to sort, the others come with `Continue`.
+    pub fn sort(&mut self) -> Result<(), GameError> {
+        *self.using = Using::Sort(vec![]);
+        self.game.use_sort(self.using)
+    }
+}
+
+struct NoEffect;
+
+impl CapacityEffect for NoEffect {}
+
+struct Life(i8);
+
+impl CapacityEffect for Life {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        ctx.game.modify_life(self.0 as isize, LifeReason::Capacity)
+    }
+}
+
+struct Draw(i8);
+
+impl CapacityEffect for Draw {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        ctx.draw(self.0)
+    }
+}
+
+struct Destroy;
+
+impl CapacityEffect for Destroy {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        *ctx.using = Using::Destroy(ctx.card);
+        Ok(())
+    }
+}
+
+struct Double;
+
+impl CapacityEffect for Double {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        *ctx.using = Using::Double;
+        Ok(())
+    }
+}
+
+struct Copy;
+
+impl CapacityEffect for Copy {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        *ctx.using = Using::Copy;
+        Ok(())
+    }
+}
+
+struct LowerStep;
+
+impl CapacityEffect for LowerStep {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        ctx.game.step_modif += 1;
+        Ok(())
+    }
+}
+
+struct Sort;
+
+impl CapacityEffect for Sort {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        ctx.sort()
+    }
+}
+
+struct Swap(i8);
+
+impl CapacityEffect for Swap {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        *ctx.using = Using::Swap(ctx.card, self.0);
+        Ok(())
+    }
+}
+
+struct UnderDeck;
+
+impl CapacityEffect for UnderDeck {
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+        *ctx.using = Using::UnderDeck(ctx.card);
+        Ok(())
+    }
+}
+
+struct EndLife(i8);
+
+impl CapacityEffect for EndLife {
+    fn on_end_fight(&self, game: &mut Friday) -> Result<(), GameError> {
+        game.modify_life(self.0 as isize, LifeReason::Capacity)
+    }
+}
+
+struct MaxZero;
+
+impl CapacityEffect for MaxZero {
+    fn on_fight_value(&self, values: &mut Vec<isize>) {
+        values.pop();
+    }
+}
+
+struct Stop;
+
+impl CapacityEffect for Stop {
+    fn on_draw(&self, _free_draws_left: u8) -> u8 {
+        0
+    }
+}
+
+/// A capacity registered on a card set, known by its name in the cards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomCapacity(Arc<str>);
+
+impl CustomCapacity {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CustomCapacity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl CardSet {
+    /// Gives `effect` to the cards of the set with the capacity
+    /// `{"Custom": name}`.
+    ///
+    /// Cards whose custom capacity was never registered have no effect.
+    pub fn register_capacity(&mut self, name: &str, effect: Box<dyn CapacityEffect>) -> Capacity {
+        self.capacities.set(name, effect);
+        Capacity::Custom(CustomCapacity(name.into()))
+    }
+}
+
+impl Capacity {
+    /// Calls `f` with the effect of the capacity, custom ones being looked up
+    /// in `set`.
+    pub fn with_effect<R, F>(&self, set: &CardSet, f: F) -> R
+    where
+        F: FnOnce(&dyn CapacityEffect) -> R,
+    {
+        match self {
+            Capacity::None => f(&NoEffect),
+            Capacity::Life(n) => f(&Life(*n)),
+            Capacity::Card(n) => f(&Draw(*n)),
+            Capacity::Destroy => f(&Destroy),
+            Capacity::Double => f(&Double),
+            Capacity::Copy => f(&Copy),
+            Capacity::LowerStep => f(&LowerStep),
+            Capacity::Sort => f(&Sort),
+            Capacity::Swap(n) => f(&Swap(*n)),
+            Capacity::UnderDeck => f(&UnderDeck),
+            Capacity::EndLife(n) => f(&EndLife(*n)),
+            Capacity::MaxZero => f(&MaxZero),
+            Capacity::Stop => f(&Stop),
+            Capacity::Custom(c) => f(set.capacities.get(c.name()).unwrap_or(&NoEffect)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::FridayBuilder;
+    use crate::friday::Event;
+
+    struct Heal;
+
+    impl CapacityEffect for Heal {
+        fn is_active(&self) -> bool {
+            true
+        }
+
+        fn on_use(&self, ctx: &mut UseContext) -> Result<(), GameError> {
+            ctx.game.modify_life(3, LifeReason::Capacity)?;
+            ctx.draw(1)
+        }
+
+        fn on_end_fight(&self, game: &mut Friday) -> Result<(), GameError> {
+            game.modify_life(-1, LifeReason::Capacity)
+        }
+    }
+
+    #[test]
+    fn test_custom() {
+        let text = include_str!("../data/cards.json").replacen(
+            r#""Life": 2"#,
+            r#""Custom": "test-heal""#,
+            1,
+        );
+        let mut set = CardSet::from_json(text.as_bytes()).unwrap();
+        let eating = set
+            .ids(|c| c.is_starting() && c.kind.to_fighting_card().title == "eating")
+            .next()
+            .unwrap();
+        let weak = set
+            .ids(|c| c.is_starting() && c.kind.to_fighting_card().title == "weak")
+            .next()
+            .unwrap();
+        let wreck = set.ids(|c| c.is_hazard_knowledge()).next().unwrap();
+        let capacity = set[eating].kind.to_fighting_card().capacity.clone();
+        assert_eq!(capacity.to_string(), "test-heal");
+        assert!(!capacity.is_active(&set));
+
+        let heal = set.register_capacity("test-heal", Box::new(Heal));
+        assert_eq!(heal, capacity);
+        assert!(capacity.is_active(&set));
+        // the effect is only known to this set
+        let other = CardSet::from_json(text.as_bytes()).unwrap();
+        assert!(!capacity.is_active(&other));
+        let mut game = FridayBuilder::new(1)
+            .card_set(Arc::new(set))
+            .life_points(10)
+            .fighting_deck(&[weak, eating])
+            .fight(wreck)
+            .build();
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Use(Handle(1))).unwrap();
+        assert_eq!(game.life_points, 13);
+        assert_eq!(game.get_right().unwrap().len(), 1);
+        game.next(Event::Win).unwrap();
+        assert_eq!(game.life_points, 12);
+    }
+}
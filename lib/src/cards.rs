@@ -1,6 +1,15 @@
 use std::fmt;
+use std::io::{self, Read};
+use std::ops::Index;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+extern crate serde_json;
+
+use crate::capacity::{CapacityEffect, CustomCapacity};
+use crate::hazard::{CustomHazard, HazardRule};
+use crate::registry::Registry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Step {
     Green,
     Yellow,
@@ -26,7 +35,7 @@ impl Step {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Capacity {
     None,
     Life(i8),
@@ -41,13 +50,23 @@ pub enum Capacity {
     EndLife(i8),
     MaxZero,
     Stop,
+    Custom(CustomCapacity),
 }
 
-impl fmt::Display for Capacity {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Capacity {
+    /// Whether the card must be used to trigger its capacity, custom ones
+    /// being looked up in `set`.
+    pub fn is_active(&self, set: &CardSet) -> bool {
+        self.with_effect(set, |e| e.is_active())
+    }
+}
+
+impl Capacity {
+    /// The text printed on the cards, if the game has this capacity.
+    pub fn text(&self) -> Option<&'static str> {
         use self::Capacity::*;
 
-        let s = match *self {
+        let s = match self {
             None => "...",
             Life(1) => "+1 life",
             Life(2) => "+2 life",
@@ -65,13 +84,23 @@ impl fmt::Display for Capacity {
             EndLife(-2) => "-2 life",
             MaxZero => "Highest card = 0",
             Stop => "Stop",
-            c => panic!("Unknown capacity {:?}", c),
+            _ => return Option::None,
         };
-        write!(f, "{}", s)
+        Some(s)
+    }
+}
+
+impl fmt::Display for Capacity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.text(), self) {
+            (Some(s), _) => write!(f, "{}", s),
+            (None, Capacity::Custom(c)) => write!(f, "{}", c),
+            (None, _) => write!(f, "{:?}", self),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FightingCard {
     pub title: String,
     pub fighting_value: isize,
@@ -84,17 +113,7 @@ impl fmt::Display for FightingCard {
     }
 }
 
-impl FightingCard {
-    fn new(title: &str, fighting_value: isize, capacity: Capacity) -> FightingCard {
-        FightingCard {
-            title: title.to_string(),
-            fighting_value,
-            capacity,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Hazard {
     Leveled([u8; 3]),
     Pirate(u8),
@@ -103,32 +122,44 @@ pub enum Hazard {
     PirateHalf(u8),
     PirateHazard,
     PirateAdd(u8),
+    Custom(CustomHazard),
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Hazard::*;
+
+        match self {
+            Leveled(l) => write!(f, "{}/{}/{}", l[0], l[1], l[2]),
+            Pirate(l) | PirateTwiceLife(l) | PirateHalf(l) | PirateAdd(l) => write!(f, "{}", l),
+            PirateAging => write!(f, "+2 per aging card"),
+            PirateHazard => write!(f, "all hazards"),
+            Custom(c) => write!(f, "{}", c),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+impl Hazard {
+    /// Whether the hazard has a rule of its own, told by the card title.
+    pub fn is_special(&self) -> bool {
+        !matches!(self, Hazard::Leveled(_) | Hazard::Pirate(_))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HazardCard {
     pub title: String,
     pub free_cards: u8,
     pub hazard: Hazard,
 }
 
-impl HazardCard {
-    fn new(title: &str, free_cards: u8, hazard: Hazard) -> HazardCard {
-        HazardCard {
-            title: title.to_string(),
-            free_cards,
-            hazard,
-        }
-    }
-}
-
 impl fmt::Display for HazardCard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "free:{} levels:{:?}", self.free_cards, self.hazard)
+        write!(f, "free:{} levels:{}", self.free_cards, self.hazard)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CardKind {
     Starting(FightingCard),
     AgingNormal(FightingCard),
@@ -171,7 +202,7 @@ impl CardKind {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CardDescription {
     pub kind: CardKind,
     pub start_qty: usize,
@@ -185,121 +216,34 @@ impl fmt::Display for CardDescription {
 }
 
 impl CardDescription {
-    fn new(kind: CardKind, start_qty: usize, filename: &str) -> Self {
-        Self {
-            kind,
-            start_qty,
-            filename: filename.to_string(),
-        }
-    }
-
-    fn new_starting(
-        title: &str,
-        fighting_value: isize,
-        start_qty: usize,
-        filename: &str,
-    ) -> CardDescription {
-        CardDescription {
-            kind: CardKind::Starting(FightingCard::new(title, fighting_value, Capacity::None)),
-            start_qty,
-            filename: filename.to_string(),
-        }
-    }
-
-    fn new_aging_normal(
-        title: &str,
-        fighting_value: isize,
-        start_qty: usize,
-        filename: &str,
-    ) -> CardDescription {
-        CardDescription {
-            kind: CardKind::AgingNormal(FightingCard::new(title, fighting_value, Capacity::None)),
-            start_qty,
-            filename: filename.to_string(),
-        }
-    }
-
-    fn new_aging_difficult(
-        title: &str,
-        fighting_value: isize,
-        start_qty: usize,
-        filename: &str,
-    ) -> CardDescription {
-        CardDescription {
-            kind: CardKind::AgingDifficult(FightingCard::new(
-                title,
-                fighting_value,
-                Capacity::None,
-            )),
-            start_qty,
-            filename: filename.to_string(),
-        }
-    }
-
-    fn new_hazard_knowledge(
-        hazard: HazardCard,
-        knowledge: FightingCard,
-        start_qty: usize,
-        filename: &str,
-    ) -> CardDescription {
-        CardDescription {
-            kind: CardKind::HazardKnowledge(hazard, knowledge),
-            start_qty,
-            filename: filename.to_string(),
-        }
-    }
-
-    fn new_pirate(title: &str, free_cards: u8, hazard: Hazard, filename: &str) -> CardDescription {
-        CardDescription {
-            kind: CardKind::Pirate(HazardCard::new(title, free_cards, hazard)),
-            start_qty: 1,
-            filename: filename.to_string(),
-        }
-    }
-
     pub fn is_starting(&self) -> bool {
-        // FIXME is there a simpler way for is_starting/is_pirate ? ...
-        match self.kind {
-            CardKind::Starting(..) => true,
-            _ => false,
-        }
+        matches!(self.kind, CardKind::Starting(..))
     }
 
     pub fn is_pirate(&self) -> bool {
-        match self.kind {
-            CardKind::Pirate(..) => true,
-            _ => false,
-        }
+        matches!(self.kind, CardKind::Pirate(..))
     }
 
     pub fn is_aging_normal(&self) -> bool {
-        match self.kind {
-            CardKind::AgingNormal(..) => true,
-            _ => false,
-        }
+        matches!(self.kind, CardKind::AgingNormal(..))
     }
 
     pub fn is_aging_difficult(&self) -> bool {
-        match self.kind {
-            CardKind::AgingDifficult(..) => true,
-            _ => false,
-        }
+        matches!(self.kind, CardKind::AgingDifficult(..))
     }
 
     pub fn is_hazard_knowledge(&self) -> bool {
-        match self.kind {
-            CardKind::HazardKnowledge(..) => true,
-            _ => false,
-        }
+        matches!(self.kind, CardKind::HazardKnowledge(..))
     }
 
     pub fn is_very_stupid(&self) -> bool {
-        match self.kind {
+        matches!(
+            self.kind,
             CardKind::AgingNormal(FightingCard {
-                fighting_value: -3, ..
-            }) => true,
-            _ => false,
-        }
+                fighting_value: -3,
+                ..
+            })
+        )
     }
 
     pub fn get_destroy_value(&self) -> Option<isize> {
@@ -311,29 +255,61 @@ impl CardDescription {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Card<'a> {
-    pub description: &'a CardDescription,
+/// Index of a card description in its `CardSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CardId(pub u8);
+
+/// A card drawn from the fighting deck, unique in its game.
+///
+/// Events target the cards of a fight by handle, as their positions change.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Handle(pub u32);
+
+impl fmt::Display for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Card {
+    pub id: CardId,
+    /// Given when the card is drawn, 0 before that.
+    #[serde(default)]
+    pub handle: Handle,
+    /// Not saved: given back from the card set of the game on load.
+    #[serde(skip)]
+    description: Option<Arc<CardDescription>>,
     pub tapped: bool,
     pub destroy: bool,
     pub double: bool,
 }
 
-impl<'a> Card<'a> {
-    pub fn new(description: &'a CardDescription) -> Self {
+impl Card {
+    pub fn new(id: CardId, description: Arc<CardDescription>) -> Self {
         Self {
-            description,
+            id,
+            handle: Handle::default(),
+            description: Some(description),
             tapped: false,
             destroy: false,
             double: false,
         }
     }
 
+    pub fn description(&self) -> &CardDescription {
+        self.description
+            .as_ref()
+            .expect("card description not attached")
+    }
+
     pub fn get_fighting_value(&self) -> isize {
         if self.destroy {
             return 0;
         }
-        let mut val = self.description.kind.to_fighting_card().fighting_value as isize;
+        let mut val = self.description().kind.to_fighting_card().fighting_value;
         if self.double {
             val *= 2;
         }
@@ -346,10 +322,10 @@ impl<'a> Card<'a> {
     }
 }
 
-impl<'a> fmt::Display for Card<'a> {
+impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut str = String::new();
-        str.push_str(&self.description.to_string());
+        str.push_str(&self.description().to_string());
         if self.tapped {
             str.push_str(" tapped");
         }
@@ -363,226 +339,114 @@ impl<'a> fmt::Display for Card<'a> {
     }
 }
 
-fn all_card_description() -> [CardDescription; 48] {
-    use self::CardKind::*;
-
-    [
-        CardDescription::new(
-            Starting(FightingCard::new("eating", 0, Capacity::Life(2))),
-            1,
-            "friday-030.jpg",
-        ),
-        CardDescription::new_starting("genius", 2, 1, "friday-033.jpg"),
-        CardDescription::new_starting("focused", 1, 3, "friday-031.jpg"),
-        CardDescription::new_starting("weak", 0, 8, "friday-038.jpg"),
-        CardDescription::new_starting("distracted", -1, 5, "friday-043.jpg"),
-        CardDescription::new_aging_normal("stupid", -2, 2, "friday-058.jpg"),
-        CardDescription::new(
-            AgingNormal(FightingCard::new("very tired", 0, Capacity::Stop)),
-            1,
-            "friday-057.jpg",
-        ),
-        CardDescription::new_aging_normal("very stupid", -3, 1, "friday-056.jpg"),
-        CardDescription::new_aging_normal("distracted", -1, 1, "friday-055.jpg"),
-        CardDescription::new(
-            AgingNormal(FightingCard::new("afraid", 0, Capacity::MaxZero)),
-            2,
-            "friday-053.jpg",
-        ),
-        CardDescription::new(
-            AgingNormal(FightingCard::new("hungry", 0, Capacity::EndLife(-1))),
-            1,
-            "friday-051.jpg",
-        ),
-        CardDescription::new(
-            AgingNormal(FightingCard::new("very hungry", 0, Capacity::EndLife(-2))),
-            1,
-            "friday-048.jpg",
-        ),
-        CardDescription::new_aging_difficult("self homicidal", -5, 1, "friday-049.jpg"),
-        CardDescription::new_aging_difficult("idiot", -4, 1, "friday-050.jpg"),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("reader", 0, Capacity::LowerStep),
-            1,
-            "friday-009.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("trick", 0, Capacity::UnderDeck),
-            1,
-            "friday-022.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("knowledge", 0, Capacity::Destroy),
-            1,
-            "friday-011.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("mimicry", 0, Capacity::Copy),
-            1,
-            "friday-016.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("nutriment", 0, Capacity::Life(1)),
-            2,
-            "friday-027.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("equipment", 0, Capacity::Card(2)),
-            2,
-            "friday-000.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wreck boat", 1, Hazard::Leveled([0, 1, 3])),
-            FightingCard::new("strategy", 0, Capacity::Swap(2)),
-            2,
-            "friday-001.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("mimicry", 1, Capacity::Copy),
-            1,
-            "friday-015.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("mimicry", 1, Capacity::Destroy),
-            1,
-            "friday-023.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("repetition", 1, Capacity::Double),
-            1,
-            "friday-017.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("repetition", 1, Capacity::UnderDeck),
-            1,
-            "friday-029.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("nutriment", 1, Capacity::Life(1)),
-            2,
-            "friday-025.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring the island", 2, Hazard::Leveled([1, 3, 6])),
-            FightingCard::new("weapon", 2, Capacity::None),
-            2,
-            "friday-014.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("experience", 2, Capacity::Card(1)),
-            1,
-            "friday-012.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("knowledge", 2, Capacity::Destroy),
-            1,
-            "friday-003.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("vision", 2, Capacity::Sort),
-            1,
-            "friday-002.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("strategy", 2, Capacity::Swap(2)),
-            1,
-            "friday-013.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("nutriment", 2, Capacity::Life(1)),
-            1,
-            "friday-005.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Exploring deep the island", 3, Hazard::Leveled([2, 5, 8])),
-            FightingCard::new("repetition", 2, Capacity::Double),
-            1,
-            "friday-008.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wild animals", 4, Hazard::Leveled([4, 7, 11])),
-            FightingCard::new("knowledge", 3, Capacity::Destroy),
-            1,
-            "friday-018.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wild animals", 4, Hazard::Leveled([4, 7, 11])),
-            FightingCard::new("experience", 3, Capacity::Card(1)),
-            1,
-            "friday-019.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wild animals", 4, Hazard::Leveled([4, 7, 11])),
-            FightingCard::new("vision", 3, Capacity::Sort),
-            1,
-            "friday-004.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Wild animals", 4, Hazard::Leveled([4, 7, 11])),
-            FightingCard::new("strategy", 3, Capacity::Swap(2)),
-            1,
-            "friday-024.jpg",
-        ),
-        CardDescription::new_hazard_knowledge(
-            HazardCard::new("Cannibal", 5, Hazard::Leveled([5, 9, 14])),
-            FightingCard::new("weapon", 4, Capacity::None),
-            2,
-            "friday-006.jpg",
-        ),
-        CardDescription::new_pirate("", 10, Hazard::Pirate(40), "friday-061.jpg"),
-        CardDescription::new_pirate(
-            "Each additonal fighting card costs 2 life points",
-            7,
-            Hazard::PirateTwiceLife(16),
-            "friday-062.jpg",
-        ),
-        CardDescription::new_pirate("", 8, Hazard::Pirate(30), "friday-063.jpg"),
-        CardDescription::new_pirate("", 6, Hazard::Pirate(20), "friday-064.jpg"),
-        CardDescription::new_pirate(
-            "+2 hazard points for each aging card added to your Robinson stack",
-            5,
-            Hazard::PirateAging,
-            "friday-065.jpg",
-        ),
-        CardDescription::new_pirate(
-            "Only half of the fighting cards count (aging cards must be part of this)",
-            9,
-            Hazard::PirateHalf(22),
-            "friday-066.jpg",
-        ),
-        CardDescription::new_pirate(
-            "Fight against all remaining hazard cards",
-            0,
-            Hazard::PirateHazard,
-            "friday-067.jpg",
-        ),
-        CardDescription::new_pirate("", 7, Hazard::Pirate(25), "friday-068.jpg"),
-        CardDescription::new_pirate("", 9, Hazard::Pirate(35), "friday-069.jpg"),
-        CardDescription::new_pirate(
-            "Each drawn fighting card counts +1 fighting point",
-            10,
-            Hazard::PirateAdd(52),
-            "friday-070.jpg",
-        ),
-    ]
+/// The cards a game is played with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardSet {
+    cards: Vec<Arc<CardDescription>>,
+    /// Effects of the custom capacities, see `register_capacity()`.
+    #[serde(skip)]
+    pub(crate) capacities: Registry<dyn CapacityEffect>,
+    /// Rules of the custom hazards, see `register_hazard()`.
+    #[serde(skip)]
+    pub(crate) hazards: Registry<dyn HazardRule>,
 }
 
 lazy_static! {
-    pub static ref CARDS: [CardDescription; 48] = all_card_description();
+    static ref BUILTIN: Arc<CardSet> =
+        Arc::new(CardSet::from_json(&include_bytes!("../data/cards.json")[..]).unwrap());
+}
+
+impl CardSet {
+    /// The cards of the original game.
+    pub fn builtin() -> Arc<CardSet> {
+        BUILTIN.clone()
+    }
+
+    /// Reads a card set, as a JSON list of `CardDescription`s.
+    pub fn from_json<R: Read>(reader: R) -> io::Result<Self> {
+        let set: CardSet = serde_json::from_reader(reader)?;
+        if set.cards.len() > u8::MAX as usize + 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("too many cards: {}", set.cards.len()),
+            ));
+        }
+        Ok(set)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Ids of the card descriptions matching `f`.
+    pub fn ids<'a, F>(&'a self, f: F) -> impl Iterator<Item = CardId> + 'a
+    where
+        F: Fn(&CardDescription) -> bool + 'a,
+    {
+        self.cards
+            .iter()
+            .enumerate()
+            .filter(move |(_, c)| f(c))
+            .map(|(i, _)| CardId(i as u8))
+    }
+
+    /// A new card, untapped.
+    pub fn card(&self, id: CardId) -> Card {
+        Card::new(id, self.cards[id.0 as usize].clone())
+    }
+
+    /// Gives `cards` their description, once deserialized.
+    pub fn attach(&self, cards: &mut [Card]) -> Result<(), CardId> {
+        for card in cards {
+            let description = self.cards.get(card.id.0 as usize).ok_or(card.id)?;
+            card.description = Some(description.clone());
+        }
+        Ok(())
+    }
+}
+
+impl Index<CardId> for CardSet {
+    type Output = CardDescription;
+
+    fn index(&self, id: CardId) -> &CardDescription {
+        &self.cards[id.0 as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::friday::Friday;
+    use crate::rules::RuleSet;
+
+    #[test]
+    fn test_card_set() {
+        let builtin = CardSet::builtin();
+        assert_eq!(builtin.len(), 48);
+
+        // a variant where genius is worth 5
+        let mut json = serde_json::to_value(&*builtin).unwrap();
+        let genius = builtin
+            .ids(|c| c.is_starting() && c.kind.to_fighting_card().title == "genius")
+            .next()
+            .unwrap();
+        json["cards"][genius.0 as usize]["kind"]["Starting"]["fighting_value"] = 5.into();
+        let variant = CardSet::from_json(json.to_string().as_bytes()).unwrap();
+        assert_eq!(variant[genius].kind.to_fighting_card().fighting_value, 5);
+
+        let game = Friday::with_rules(RuleSet::level(1).unwrap(), 3, Arc::new(variant)).unwrap();
+        let card = game.fighting_deck.iter().find(|c| c.id == genius).unwrap();
+        assert_eq!(card.get_fighting_value(), 5);
+        // the rest of the game is dealt as with the built-in set
+        let ids = |game: &Friday| game.fighting_deck.iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(ids(&game), ids(&Friday::with_seed(1, 3).unwrap()));
+
+        let err = CardSet::from_json(&br#"{"cards": [{"kind": "Foo"}]}"#[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
@@ -0,0 +1,134 @@
+use crate::cards::{Card, CardKind, Handle};
+use crate::friday::State;
+use crate::strategy::card_score;
+use crate::view::PlayerView;
+
+/// What to get rid of first when losing a fight.
+///
+/// Each card is worth how much it hurts the deck: its negative fighting
+/// value and aging penalties, plus the bonus of its kind. Cards worth
+/// nothing are kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiscardPriorities {
+    /// Bonus for destroying an aging card.
+    pub aging: isize,
+    /// Bonus for destroying a starting card of 0 or less.
+    pub weak_starting: isize,
+    /// Never destroy the knowledge cards won from hazards.
+    pub keep_knowledge: bool,
+}
+
+impl Default for DiscardPriorities {
+    fn default() -> Self {
+        Self {
+            aging: 3,
+            weak_starting: 1,
+            keep_knowledge: true,
+        }
+    }
+}
+
+impl DiscardPriorities {
+    fn worth(&self, card: &Card) -> isize {
+        let harm = -card_score(card);
+        match card.description().kind {
+            CardKind::AgingNormal(_) | CardKind::AgingDifficult(_) => self.aging + harm.max(0),
+            CardKind::Starting(_) if harm >= 0 => self.weak_starting + harm,
+            CardKind::HazardKnowledge(..) if !self.keep_knowledge => harm,
+            _ => 0,
+        }
+    }
+}
+
+/// The best cards to destroy when losing the current fight, for
+/// `Event::Lose`.
+///
+/// Destroy values add up to the life lost at most. Returns `None` out of a
+/// fight or against a pirate.
+pub fn plan_discard(view: &PlayerView, priorities: &DiscardPriorities) -> Option<Vec<Handle>> {
+    let cards: Vec<&Card> = match &view.state {
+        State::Fighting(c, _, left, right, _) if !c.description().is_pirate() => {
+            left.iter().chain(right.iter()).collect()
+        }
+        _ => return None,
+    };
+    let budget = view.fight_diff?.min(0).unsigned_abs();
+
+    // knapsack over the life lost: best[b] is the best worth and cards
+    // destroyed for a cost of b at most
+    let mut best: Vec<(isize, Vec<Handle>)> = vec![(0, vec![]); budget + 1];
+    for card in cards.iter() {
+        let worth = priorities.worth(card);
+        if card.destroy || worth <= 0 {
+            continue;
+        }
+        let cost = card.description().get_destroy_value().unwrap() as usize;
+        for b in (cost..=budget).rev() {
+            let w = best[b - cost].0 + worth;
+            if w > best[b].0 {
+                let mut plan = best[b - cost].1.clone();
+                plan.push(card.handle);
+                best[b] = (w, plan);
+            }
+        }
+    }
+    best.pop().map(|(_, plan)| plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::FridayBuilder;
+    use crate::cards::{CardDescription, CardId, CardSet};
+    use crate::friday::Event;
+
+    fn find<F: Fn(&CardDescription) -> bool>(f: F) -> CardId {
+        CardSet::builtin().ids(f).next().unwrap()
+    }
+
+    fn fighting(title: &str) -> CardId {
+        find(|c| !c.is_pirate() && c.kind.to_fighting_card().title == title)
+    }
+
+    #[test]
+    fn test_plan_discard() {
+        let island = find(|c| {
+            c.is_hazard_knowledge() && c.kind.to_hazard_card().title == "Exploring the island"
+        });
+        let mut game = FridayBuilder::new(1)
+            .life_points(10)
+            .fighting_deck(&[
+                fighting("genius"),
+                fighting("stupid"),
+                fighting("distracted"),
+                fighting("weak"),
+            ])
+            .fight(island)
+            .build();
+        // weak and distracted for free, then stupid and genius
+        for _ in 0..4 {
+            game.next(Event::Fight).unwrap();
+        }
+        assert_eq!(game.fight_diff(), Some(-2));
+
+        // the aging card costs the whole budget
+        let plan = plan_discard(&game.player_view(), &DiscardPriorities::default()).unwrap();
+        assert_eq!(plan, vec![Handle(3)]);
+        let starting = DiscardPriorities {
+            aging: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_discard(&game.player_view(), &starting).unwrap(),
+            vec![Handle(1), Handle(2)]
+        );
+
+        game.next(Event::Lose(plan)).unwrap();
+        assert_eq!(game.destroyed[0].id, fighting("stupid"));
+        assert_eq!(
+            plan_discard(&game.player_view(), &DiscardPriorities::default()),
+            None
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::error;
+use std::fmt;
+
+use crate::cards::Handle;
+use crate::friday::Event;
+use crate::rules::RuleError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    /// No hazard or pirate at this index.
+    InvalidIndex(usize),
+    /// No card with this handle in the fight, or no more.
+    UnknownCard(Handle),
+    /// Both hazard cards are left, one must be fought.
+    MustChoose,
+    CardAlreadyTapped(Handle),
+    CardDestroyed(Handle),
+    CardAlreadyDoubled(Handle),
+    /// The card has no capacity that can be used.
+    NoCapacity(Handle),
+    /// A card can't be the target of its own capacity.
+    SelfTarget(Handle),
+    /// Only the free cards on the left can be replaced from below the stack.
+    CannotReplace(Handle),
+    SortFull,
+    InvalidSort(Vec<usize>),
+    MustDrawFirst,
+    /// The fight is not won yet, by this difference.
+    CannotWin(isize),
+    CannotLoseToPirate,
+    DiscardTooCostly {
+        cost: isize,
+        diff: isize,
+    },
+    UnexpectedEvent(Event),
+    NothingToUndo,
+    NothingToRedo,
+    /// The game is over: life went below zero.
+    PlayerDied,
+    /// The game is over: no fighting card is left to draw.
+    DeckExhausted,
+    /// The game can't be dealt with these rules.
+    Rules(RuleError),
+}
+
+impl GameError {
+    /// Whether the error ended the game, rather than rejecting a move.
+    pub fn is_game_over(&self) -> bool {
+        matches!(self, GameError::PlayerDied | GameError::DeckExhausted)
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::GameError::*;
+
+        match self {
+            InvalidIndex(i) => write!(f, "Invalid choice #{}", i),
+            UnknownCard(h) => write!(f, "No card #{} in the fight", h),
+            MustChoose => write!(f, "You must choose a card"),
+            CardAlreadyTapped(i) => write!(f, "Card #{} already used", i),
+            CardDestroyed(i) => write!(f, "Card #{} is to be destroyed", i),
+            CardAlreadyDoubled(i) => write!(f, "Card #{} already doubled", i),
+            NoCapacity(i) => write!(f, "Card #{} has nothing to use", i),
+            SelfTarget(i) => write!(f, "Card #{} can't target itself", i),
+            CannotReplace(i) => write!(f, "Can't replace card #{} from the right pile", i),
+            SortFull => write!(f, "Already drawn 3 cards"),
+            InvalidSort(order) => write!(f, "Invalid sort {:?}", order),
+            MustDrawFirst => write!(f, "You must draw at least one card"),
+            CannotWin(diff) => write!(f, "It's not possible to win! (diff: {})", diff),
+            CannotLoseToPirate => write!(f, "Arrr, you can't decide to lose against a pirate!"),
+            DiscardTooCostly { cost, diff } => {
+                write!(f, "Not enough diff:{} for this much discard:{}", diff, cost)
+            }
+            UnexpectedEvent(e) => write!(f, "Wrong event for the current state: {:?}", e),
+            NothingToUndo => write!(f, "Nothing to undo"),
+            NothingToRedo => write!(f, "Nothing to redo"),
+            PlayerDied => write!(f, "You died!"),
+            DeckExhausted => write!(f, "Fighting deck is empty"),
+            Rules(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for GameError {}
+
+impl From<RuleError> for GameError {
+    fn from(err: RuleError) -> Self {
+        GameError::Rules(err)
+    }
+}
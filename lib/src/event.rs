@@ -0,0 +1,168 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cards::Handle;
+use crate::friday::Event;
+
+/// Each of `v` after a space.
+fn words<T: fmt::Display>(v: &[T]) -> String {
+    v.iter().map(|w| format!(" {}", w)).collect()
+}
+
+/// The text form of events, one per line in transcripts:
+///
+/// `start`, `hazard 1`, `hazard skip`, `pirate 0`, `choose 3`, `under 3`,
+/// `under 3 replace`, `fight`, `use 3`, `win`, `lose 2 5`, `continue`,
+/// `break`, `sort 2 0 1` and `sort discard 2 0 1`.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Start => write!(f, "start"),
+            Event::HazardChoice(Some(i)) => write!(f, "hazard {}", i),
+            Event::HazardChoice(None) => write!(f, "hazard skip"),
+            Event::PirateChoice(i) => write!(f, "pirate {}", i),
+            Event::Choice(h) => write!(f, "choose {}", h),
+            Event::ChoiceUnder(h, false) => write!(f, "under {}", h),
+            Event::ChoiceUnder(h, true) => write!(f, "under {} replace", h),
+            Event::Fight => write!(f, "fight"),
+            Event::Use(h) => write!(f, "use {}", h),
+            Event::Win => write!(f, "win"),
+            Event::Lose(v) => write!(f, "lose{}", words(v)),
+            Event::Continue => write!(f, "continue"),
+            Event::Break => write!(f, "break"),
+            Event::Sort(order, destroy) => write!(
+                f,
+                "sort{}{}",
+                if *destroy { " discard" } else { "" },
+                words(order)
+            ),
+        }
+    }
+}
+
+/// A text that is not an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseEventError(pub String);
+
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid event \"{}\"", self.0)
+    }
+}
+
+impl error::Error for ParseEventError {}
+
+impl FromStr for Event {
+    type Err = ParseEventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseEventError(s.to_string());
+        let mut parts = s.split_whitespace();
+        let name = parts.next().ok_or_else(err)?;
+        let args: Vec<&str> = parts.collect();
+        let numbers = |args: &[&str]| {
+            args.iter()
+                .map(|a| a.parse::<usize>().map_err(|_| err()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let handles = |args: &[&str]| {
+            args.iter()
+                .map(|a| a.parse().map(Handle).map_err(|_| err()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let one = |args: &[&str]| match numbers(args)?[..] {
+            [n] => Ok(n),
+            _ => Err(err()),
+        };
+        let handle = |args: &[&str]| match handles(args)?[..] {
+            [h] => Ok(h),
+            _ => Err(err()),
+        };
+
+        let event = match (name, &args[..]) {
+            ("start", []) => Event::Start,
+            ("hazard", ["skip"]) => Event::HazardChoice(None),
+            ("hazard", args) => Event::HazardChoice(Some(one(args)?)),
+            ("pirate", args) => Event::PirateChoice(one(args)?),
+            ("choose", args) => Event::Choice(handle(args)?),
+            ("under", [h, "replace"]) => Event::ChoiceUnder(handle(&[h])?, true),
+            ("under", args) => Event::ChoiceUnder(handle(args)?, false),
+            ("fight", []) => Event::Fight,
+            ("use", args) => Event::Use(handle(args)?),
+            ("win", []) => Event::Win,
+            ("lose", args) => Event::Lose(handles(args)?),
+            ("continue", []) => Event::Continue,
+            ("break", []) => Event::Break,
+            ("sort", ["discard", args @ ..]) => Event::Sort(numbers(args)?, true),
+            ("sort", args) => Event::Sort(numbers(args)?, false),
+            _ => return Err(err()),
+        };
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_forms() {
+        let forms = [
+            (Event::Start, "start", r#""Start""#),
+            (
+                Event::HazardChoice(Some(1)),
+                "hazard 1",
+                r#"{"HazardChoice":1}"#,
+            ),
+            (
+                Event::HazardChoice(None),
+                "hazard skip",
+                r#"{"HazardChoice":null}"#,
+            ),
+            (Event::PirateChoice(0), "pirate 0", r#"{"PirateChoice":0}"#),
+            (Event::Choice(Handle(3)), "choose 3", r#"{"Choice":3}"#),
+            (
+                Event::ChoiceUnder(Handle(3), true),
+                "under 3 replace",
+                r#"{"ChoiceUnder":[3,true]}"#,
+            ),
+            (Event::Use(Handle(12)), "use 12", r#"{"Use":12}"#),
+            (Event::Lose(vec![]), "lose", r#"{"Lose":[]}"#),
+            (
+                Event::Lose(vec![Handle(2), Handle(5)]),
+                "lose 2 5",
+                r#"{"Lose":[2,5]}"#,
+            ),
+            (
+                Event::Sort(vec![2, 0, 1], true),
+                "sort discard 2 0 1",
+                r#"{"Sort":[[2,0,1],true]}"#,
+            ),
+            (Event::Sort(vec![], false), "sort", r#"{"Sort":[[],false]}"#),
+        ];
+        for (event, text, json) in forms.iter() {
+            assert_eq!(event.to_string(), *text);
+            assert_eq!(text.parse::<Event>().as_ref(), Ok(event));
+            assert_eq!(serde_json::to_string(event).unwrap(), *json);
+            assert_eq!(&serde_json::from_str::<Event>(json).unwrap(), event);
+        }
+
+        for text in [
+            "",
+            "fly",
+            "hazard",
+            "use 1 2",
+            "use -1",
+            "win 1",
+            "under 1 2",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                text.parse::<Event>(),
+                Err(ParseEventError(text.to_string()))
+            );
+        }
+    }
+}
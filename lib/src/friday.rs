@@ -1,140 +1,442 @@
-use std::cmp::Ordering;
+use std::cmp::Reverse;
 use std::iter;
+use std::mem;
+use std::sync::Arc;
 
 extern crate rand;
-use rand::Rng;
+use rand::{Isaac64Rng, Rng, SeedableRng};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::cards::{Capacity, Card, CardKind, Hazard, HazardCard, Step, CARDS};
+use crate::capacity::UseContext;
+use crate::cards::{
+    Card, CardDescription, CardId, CardKind, CardSet, Handle, Hazard, HazardCard, Step,
+};
+use crate::error::GameError;
+use crate::listener::Listeners;
+use crate::rules::RuleSet;
 
-fn shuffle<T>(vec: &mut Vec<T>) {
-    let mut slice = vec.as_mut_slice();
-    rand::thread_rng().shuffle(&mut slice);
+/// Source of all the game randomness.
+///
+/// Each shuffle uses a generator seeded from the game seed and the number of
+/// shuffles done so far, so that the same seed always gives the same game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Shuffler {
+    seed: u64,
+    count: u64,
 }
 
-#[derive(Debug, Clone)]
-pub enum Using<'a> {
+impl Shuffler {
+    fn new(seed: u64) -> Self {
+        Self { seed, count: 0 }
+    }
+
+    fn shuffle<T>(&mut self, slice: &mut [T]) {
+        let mut rng = Isaac64Rng::from_seed(&[self.seed, self.count][..]);
+        self.count += 1;
+        rng.shuffle(slice);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Using {
     None,
     Draw(i8),
-    Destroy(usize),
+    Destroy(Handle),
     Double,
     Copy,
-    Sort(Vec<Card<'a>>),
-    Swap(usize, i8),
-    UnderDeck(usize),
+    Sort(Vec<Card>),
+    Swap(Handle, i8),
+    UnderDeck(Handle),
 }
 
-#[derive(Debug, Clone)]
-pub enum State<'a> {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum State {
     None,
-    ChooseHazard(Vec<Card<'a>>),
+    ChooseHazard(Vec<Card>),
     ChoosePirate,
-    Fighting(Card<'a>, u8, Vec<Card<'a>>, Vec<Card<'a>>, Using<'a>),
+    Fighting(Card, u8, Vec<Card>, Vec<Card>, Using),
     Ended(bool),
 }
 
-#[derive(Debug)]
-pub enum Event<'a> {
+impl State {
+    /// Gives the cards their description from `set`, once deserialized.
+    pub fn attach(&mut self, set: &CardSet) -> Result<(), CardId> {
+        match self {
+            State::ChooseHazard(cards) => set.attach(cards),
+            State::Fighting(c, _, left, right, using) => {
+                set.attach(std::slice::from_mut(c))?;
+                set.attach(left)?;
+                set.attach(right)?;
+                match using {
+                    Using::Sort(cards) => set.attach(cards),
+                    _ => Ok(()),
+                }
+            }
+            State::None | State::ChoosePirate | State::Ended(_) => Ok(()),
+        }
+    }
+}
+
+/// A move of the player, or the start of a fight.
+///
+/// Events own their data, so that they can be logged, queued or sent. They
+/// have a text form, see `Display` and `FromStr`, and a JSON one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
     Start,
     HazardChoice(Option<usize>),
-    Choice(usize),
-    ChoiceUnder(usize, bool),
+    PirateChoice(usize),
+    Choice(Handle),
+    ChoiceUnder(Handle, bool),
     Fight,
-    Use(usize),
+    Use(Handle),
     Win,
-    Lose(&'a mut [usize]),
+    Lose(Vec<Handle>),
     Continue,
     Break,
     Sort(Vec<usize>, bool),
 }
 
-#[derive(Debug)]
-pub struct Friday<'a> {
-    pub level: Level,
+/// Why the life points changed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LifeReason {
+    /// A card drawn beyond the free ones.
+    DrawCost,
+    /// The difference of a lost fight.
+    FightLost,
+    /// A card capacity, when used or at the end of the fight.
+    Capacity,
+}
+
+/// What an event changed in the game, as returned by `Friday::next()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    /// Hazard cards drawn to choose from.
+    HazardsDrawn(Vec<CardId>),
+    /// A card drawn from the fighting deck.
+    CardDrawn(CardId),
+    LifeChanged {
+        delta: isize,
+        reason: LifeReason,
+    },
+    /// An aging card added to the fighting deck, before it is reshuffled.
+    AgingAdded(CardId),
+    /// The fighting discard shuffled back into the fighting deck.
+    DeckReshuffled,
+    /// The hazard deck ran out and was reshuffled, for this step.
+    StepAdvanced(Step),
+    CardDestroyed(CardId),
+    FightWon(CardId),
+    FightLost(CardId),
+    GameEnded {
+        won: bool,
+    },
+}
+
+/// Deserializing a game gives its cards their description from its card
+/// set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct Friday {
+    pub rules: RuleSet,
     pub life_points: isize,
     pub step: Step,
     pub step_modif: usize,
-    pub destroyed: Vec<Card<'a>>,
-    pub aging_deck: Vec<Card<'a>>,
-    pub fighting_deck: Vec<Card<'a>>,
-    pub fighting_discard: Vec<Card<'a>>,
-    pub hazard_deck: Vec<Card<'a>>,
-    pub hazard_discard: Vec<Card<'a>>,
-    pub pirate_cards: Vec<Card<'a>>,
-    pub pirate_cards_won: Vec<Card<'a>>,
-    pub state: State<'a>,
+    pub destroyed: Vec<Card>,
+    pub aging_deck: Vec<Card>,
+    pub fighting_deck: Vec<Card>,
+    pub fighting_discard: Vec<Card>,
+    pub hazard_deck: Vec<Card>,
+    pub hazard_discard: Vec<Card>,
+    pub pirate_cards: Vec<Card>,
+    pub pirate_cards_won: Vec<Card>,
+    pub state: State,
+    pub card_set: Arc<CardSet>,
+    rng: Shuffler,
+    log: Vec<Event>,
+    #[serde(default)]
+    redo: Vec<Event>,
+    /// Number of cards on top of the fighting deck put there by a sort.
+    #[serde(default)]
+    known_top: usize,
+    /// Number of cards at the bottom of the fighting deck put there from a
+    /// fight.
+    #[serde(default)]
+    known_bottom: usize,
+    /// Number of cards drawn from the fighting deck, to give them handles.
+    #[serde(default)]
+    drawn: u32,
+    /// Effects of the event being handled.
+    #[serde(skip)]
+    pub(crate) effects: Vec<Effect>,
+    #[serde(skip)]
+    pub(crate) listeners: Listeners,
+    /// The game as it started, when not dealt from the seed alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<Box<Friday>>,
+}
+
+impl Serialize for Friday {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Friday::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Friday {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut game = Friday::deserialize(deserializer)?;
+        game.attach_cards()
+            .map_err(|id| D::Error::custom(format!("unknown card {}", id.0)))?;
+        Ok(game)
+    }
+}
+
+/// The cards of `set` matching `f`, each in its starting quantity.
+fn deal_cards<F>(set: &CardSet, f: F) -> Vec<Card>
+where
+    F: Fn(&CardDescription) -> bool,
+{
+    set.ids(f)
+        .flat_map(|id| iter::repeat_n(id, set[id].start_qty))
+        .map(|id| set.card(id))
+        .collect()
+}
+
+/// Position of the card `handle` in a fight, left pile first.
+fn position(left: &[Card], right: &[Card], handle: Handle) -> Result<usize, GameError> {
+    left.iter()
+        .chain(right.iter())
+        .position(|c| c.handle == handle)
+        .ok_or(GameError::UnknownCard(handle))
+}
+
+/// Whether `c` is one of the aging cards played with `rules`.
+pub(crate) fn is_aging_at(c: &CardDescription, rules: &RuleSet) -> bool {
+    c.is_aging_difficult() || (c.is_aging_normal() && (rules.very_stupid || !c.is_very_stupid()))
 }
 
-type Level = usize;
+impl Friday {
+    /// Creates a game at `level`, from 1 to 4.
+    pub fn new(level: usize) -> Result<Self, GameError> {
+        Self::with_seed(level, rand::thread_rng().gen())
+    }
+
+    /// Creates a game whose shuffles are all derived from `seed`.
+    ///
+    /// The same seed and the same sequence of events always give the same game.
+    pub fn with_seed(level: usize, seed: u64) -> Result<Self, GameError> {
+        let rules = RuleSet::level(level)?;
+        Self::with_rules(rules, seed, CardSet::builtin())
+    }
 
-impl<'a> Friday<'a> {
-    pub fn new(level: Level) -> Self {
+    /// Like `with_seed()`, but played with house rules and the cards of
+    /// `card_set`.
+    pub fn with_rules(
+        rules: RuleSet,
+        seed: u64,
+        card_set: Arc<CardSet>,
+    ) -> Result<Self, GameError> {
+        rules.validate(&card_set)?;
+        let mut friday = Self::deal(rules, seed, card_set);
+        friday.handle_event(&Event::Start).unwrap();
+        Ok(friday)
+    }
+
+    /// Deals the decks, before the first hazards are drawn.
+    pub(crate) fn deal(rules: RuleSet, seed: u64, card_set: Arc<CardSet>) -> Self {
+        let mut rng = Shuffler::new(seed);
         let mut friday = Self {
-            level,
-            life_points: if level == 4 { 18 } else { 20 },
+            life_points: rules.life_points as isize,
             step: Step::Green,
             step_modif: 0,
             destroyed: vec![],
-            aging_deck: Self::make_aging_deck(level),
-            fighting_deck: Self::make_fighting_deck(),
+            aging_deck: Self::make_aging_deck(&card_set, &rules, &mut rng),
+            fighting_deck: Self::make_fighting_deck(&card_set, &mut rng),
             fighting_discard: vec![],
-            hazard_deck: Self::make_hazard_deck(),
+            hazard_deck: Self::make_hazard_deck(&card_set, &mut rng),
             hazard_discard: vec![],
-            pirate_cards: Self::take_two_pirate_cards(),
+            pirate_cards: Self::take_pirate_cards(&card_set, rules.pirates, &mut rng),
             pirate_cards_won: vec![],
             state: State::None,
+            rng,
+            log: vec![],
+            redo: vec![],
+            known_top: 0,
+            known_bottom: 0,
+            drawn: 0,
+            effects: vec![],
+            listeners: Listeners::default(),
+            start: None,
+            card_set,
+            rules,
         };
-        if level >= 2 {
+        for _ in 0..friday.rules.extra_aging {
             friday.aging();
         }
-        friday.next(Event::Start).unwrap();
         friday
     }
 
-    fn end_fight(&mut self, cards: &[Card<'a>]) -> Result<(), String> {
+    /// Starts a dealt game, with `fight` or by drawing the first hazards.
+    ///
+    /// The game is remembered as is, so that `undo()` doesn't go back to a
+    /// game dealt from the seed alone.
+    pub(crate) fn begin(&mut self, fight: Option<Card>) {
+        match fight {
+            Some(c) => self.state = State::Fighting(c, 0, vec![], vec![], Using::None),
+            None => self.handle_event(&Event::Start).unwrap(),
+        }
+        self.start = Some(Box::new(self.clone()));
+    }
+
+    /// Rebuilds a game by playing back `events` on a fresh game.
+    ///
+    /// Together with `rules`, `seed()`, `card_set` and `events()`, this gives
+    /// a reproducible transcript of any game.
+    pub fn replay(
+        rules: RuleSet,
+        seed: u64,
+        card_set: Arc<CardSet>,
+        events: &[Event],
+    ) -> Result<Self, GameError> {
+        Self::replay_from(Self::with_rules(rules, seed, card_set)?, events)
+    }
+
+    fn replay_from(mut friday: Self, events: &[Event]) -> Result<Self, GameError> {
+        for event in events {
+            friday.next(event.clone())?;
+        }
+        Ok(friday)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.rng.seed
+    }
+
+    /// The events accepted so far, in order.
+    pub fn events(&self) -> &[Event] {
+        &self.log
+    }
+
+    /// Gives every card its description from `card_set`, once deserialized.
+    pub(crate) fn attach_cards(&mut self) -> Result<(), CardId> {
+        let set = &self.card_set;
+        for cards in [
+            &mut self.destroyed,
+            &mut self.aging_deck,
+            &mut self.fighting_deck,
+            &mut self.fighting_discard,
+            &mut self.hazard_deck,
+            &mut self.hazard_discard,
+            &mut self.pirate_cards,
+            &mut self.pirate_cards_won,
+        ]
+        .iter_mut()
+        {
+            set.attach(cards)?;
+        }
+        self.state.attach(set)
+    }
+
+    /// Rolls back the last accepted event.
+    ///
+    /// The game is replayed from its start, so the hidden deck order is
+    /// restored too.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        let mut log = self.log.clone();
+        let event = match log.pop() {
+            Some(event) => event,
+            None => return Err(GameError::NothingToUndo),
+        };
+        let start = self.start.clone();
+        let game = match &start {
+            Some(game) => (**game).clone(),
+            None => {
+                Self::with_rules(self.rules.clone(), self.seed(), self.card_set.clone()).unwrap()
+            }
+        };
+        // the copy has no listeners: the replayed events are not told again
+        let mut game = Self::replay_from(game, &log)?;
+        game.start = start;
+        game.listeners = mem::take(&mut self.listeners);
+        game.redo = mem::take(&mut self.redo);
+        game.redo.push(event);
+        *self = game;
+        Ok(())
+    }
+
+    /// Plays again the last undone event.
+    pub fn redo(&mut self) -> Result<Vec<Effect>, GameError> {
+        let event = match self.redo.pop() {
+            Some(event) => event,
+            None => return Err(GameError::NothingToRedo),
+        };
+        let redo = mem::take(&mut self.redo);
+        let res = self.next(event);
+        self.redo = redo;
+        res
+    }
+
+    pub fn is_ended(&self) -> bool {
+        matches!(self.state, State::Ended(_))
+    }
+
+    /// Discards the cards of a fight, once the state is taken out.
+    fn end_fight(&mut self, cards: Vec<Card>) -> Result<(), GameError> {
         self.step_modif = 0;
+        let mut res = Ok(());
         for c in cards {
             if c.destroy {
-                self.destroyed.push(c.clone());
+                self.effects.push(Effect::CardDestroyed(c.id));
+                self.destroyed.push(c);
             } else {
-                if let Capacity::EndLife(n) = c.description.kind.to_fighting_card().capacity {
-                    self.modify_life(n as isize)?;
+                // the cards left still go to the discard if the game ends
+                if res.is_ok() {
+                    let set = self.card_set.clone();
+                    let capacity = &c.description().kind.to_fighting_card().capacity;
+                    res = capacity.with_effect(&set, |e| e.on_end_fight(self));
                 }
-                self.fighting_discard.push(c.clone());
+                self.fighting_discard.push(c);
             }
         }
-
-        self.state = State::None;
-        self.next(Event::Start)
+        res
     }
 
     pub fn max_life_points(&self) -> u8 {
-        if self.level == 4 {
-            20
-        } else {
-            22
-        }
+        self.rules.max_life_points
     }
 
-    fn modify_life(&mut self, points: isize) -> Result<(), String> {
+    /// Adds `points` to the life points, up to the maximum.
+    pub fn modify_life(&mut self, points: isize, reason: LifeReason) -> Result<(), GameError> {
+        let before = self.life_points;
         self.life_points += points;
 
+        let died = self.life_points < 0;
         if self.life_points > self.max_life_points() as isize {
             self.life_points = self.max_life_points() as isize;
-        } else if self.life_points < 0 {
+        } else if died {
             self.life_points = 0;
+        }
+        if self.life_points != before {
+            self.effects.push(Effect::LifeChanged {
+                delta: self.life_points - before,
+                reason,
+            });
+        }
+        if died {
             self.end_game(false);
-            return Err("You died!".to_string());
+            return Err(GameError::PlayerDied);
         }
 
         Ok(())
     }
 
-    fn use_draw_card(
+    pub(crate) fn use_draw_card(
         &mut self,
         using: &mut Using,
         left: i8,
-        right: &mut Vec<Card<'a>>,
-    ) -> Result<(), String> {
+        right: &mut Vec<Card>,
+    ) -> Result<(), GameError> {
         right.push(self.fighting_deck_pop(true)?);
 
         let left = left - 1;
@@ -146,11 +448,11 @@ impl<'a> Friday<'a> {
         Ok(())
     }
 
-    fn use_sort(&mut self, using: &mut Using<'a>) -> Result<(), String> {
+    pub(crate) fn use_sort(&mut self, using: &mut Using) -> Result<(), GameError> {
         match using {
             Using::Sort(cards) => {
                 if cards.len() >= 3 {
-                    return Err("Already drawn 3 cards".to_string());
+                    return Err(GameError::SortFull);
                 }
                 cards.push(self.fighting_deck_pop(true)?);
             }
@@ -161,64 +463,105 @@ impl<'a> Friday<'a> {
 
     fn use_card(
         &mut self,
-        c: usize,
-        using: &mut Using<'a>,
-        left: &mut Vec<Card<'a>>,
-        right: &mut Vec<Card<'a>>,
-    ) -> Result<(), String> {
-        let card = left.iter_mut().chain(right.iter_mut()).nth(c).unwrap();
-        match card.description.kind.to_fighting_card().capacity {
-            Capacity::Life(lp) => {
-                self.modify_life(lp as isize)?;
-            }
-            Capacity::Card(n) => {
-                self.use_draw_card(using, n, right)?;
-            }
-            Capacity::Destroy => {
-                *using = Using::Destroy(c);
-            }
-            Capacity::Double => {
-                *using = Using::Double;
-            }
-            Capacity::Copy => {
-                *using = Using::Copy;
-            }
-            Capacity::LowerStep => {
-                self.step_modif += 1;
-            }
-            Capacity::Sort => {
-                *using = Using::Sort(vec![]);
-                self.use_sort(using)?;
-            }
-            Capacity::Swap(n) => {
-                *using = Using::Swap(c, n);
-            }
-            Capacity::UnderDeck => {
-                *using = Using::UnderDeck(c);
-            }
-            _ => {
-                *using = Using::None;
+        handle: Handle,
+        using: &mut Using,
+        left: &mut Vec<Card>,
+        right: &mut Vec<Card>,
+    ) -> Result<(), GameError> {
+        let c = position(left, right, handle)?;
+        let card = left.iter().chain(right.iter()).nth(c).unwrap();
+        let capacity = card.description().kind.to_fighting_card().capacity.clone();
+        let set = self.card_set.clone();
+        capacity.with_effect(&set, |e| {
+            e.on_use(&mut UseContext {
+                game: self,
+                card: handle,
+                using,
+                left,
+                right,
+            })
+        })
+    }
+
+    pub fn free_cards(&self, card: &HazardCard) -> u8 {
+        card.hazard
+            .with_rule(&self.card_set, |r| r.free_cards(self, card.free_cards))
+    }
+
+    /// Number of cards that can still be drawn for free in the current fight.
+    pub fn free_draws_left(&self) -> u8 {
+        match &self.state {
+            State::Fighting(c, used_free, left, _, _) => {
+                let free = self
+                    .free_cards(c.description().kind.to_hazard_card())
+                    .saturating_sub(*used_free);
+                left.iter().filter(|c| !c.destroy).fold(free, |free, c| {
+                    let capacity = &c.description().kind.to_fighting_card().capacity;
+                    capacity.with_effect(&self.card_set, |e| e.on_draw(free))
+                })
             }
+            _ => 0,
         }
-        Ok(())
     }
 
-    pub fn free_cards(&self, card: &HazardCard) -> u8 {
-        if let Hazard::PirateHazard = card.hazard {
-            return self
-                .hazard_deck
-                .iter()
-                .chain(self.hazard_discard.iter())
-                .map(|c| c.description.kind.to_hazard_card().free_cards)
-                .sum();
+    /// Life points paid for each card drawn beyond the free ones.
+    pub fn draw_cost(&self) -> isize {
+        match &self.state {
+            State::Fighting(c, _, _, _, _) => c
+                .description()
+                .kind
+                .to_hazard_card()
+                .hazard
+                .with_rule(&self.card_set, |r| r.draw_cost()),
+            _ => 1,
+        }
+    }
+
+    /// Plays `event`, and tells what it changed.
+    ///
+    /// Errors reject the event and leave the game as it was. Events that end
+    /// the game are accepted: their effects end with `Effect::GameEnded`.
+    pub fn next(&mut self, event: Event) -> Result<Vec<Effect>, GameError> {
+        self.effects.clear();
+        match self.handle_event(&event) {
+            Err(e) if !e.is_game_over() => return Err(e),
+            _ => {}
+        }
+        self.log.push(event);
+        self.redo.clear();
+        if !self.listeners.is_empty() {
+            self.notify_event();
+        }
+        Ok(mem::take(&mut self.effects))
+    }
+
+    /// Plays `event`, then starts the next fight when one is over.
+    fn handle_event(&mut self, event: &Event) -> Result<(), GameError> {
+        let from = if self.listeners.is_empty() {
+            None
+        } else {
+            Some(self.state.clone())
+        };
+        let res = self.apply_event(event);
+        if let Some(from) = from {
+            match res {
+                Err(ref e) if !e.is_game_over() => {}
+                _ => self.notify_transition(event, &from),
+            }
+        }
+        res?;
+
+        if let State::None = self.state {
+            self.handle_event(&Event::Start)?;
         }
-        card.free_cards
+        Ok(())
     }
 
-    pub fn next(&mut self, event: Event) -> Result<(), String> {
-        let mut state = self.state.clone();
-        match (&mut state, event) {
-            (State::None, Event::Start) => {
+    fn apply_event(&mut self, event: &Event) -> Result<(), GameError> {
+        // rejected events must leave the game as it was: each branch checks
+        // the event before changing the state in place
+        match (&mut self.state, event) {
+            (State::None, &Event::Start) => {
                 if let Some(cards) = self.hazard_pop() {
                     self.state = State::ChooseHazard(cards);
                 } else if self.pirate_cards.is_empty() {
@@ -230,278 +573,316 @@ impl<'a> Friday<'a> {
                     self.state = State::ChoosePirate;
                 }
             }
-            (State::ChooseHazard(ref mut h), Event::HazardChoice(c)) => {
+            (State::ChooseHazard(h), &Event::HazardChoice(c)) => {
                 if let Some(c) = c {
                     if c >= h.len() {
-                        return Err("Invalid choice".to_string());
+                        return Err(GameError::InvalidIndex(c));
                     }
                     let card = h.remove(c);
                     self.hazard_discard.append(h);
                     self.state = State::Fighting(card, 0, vec![], vec![], Using::None);
                 } else if h.len() == 1 {
+                    self.hazard_discard.append(h);
                     self.state = State::None;
-                    return self.next(Event::Start);
                 } else {
-                    return Err("You must choose a card".to_string());
+                    return Err(GameError::MustChoose);
                 }
             }
-            (State::ChoosePirate, Event::Choice(c)) => {
+            (State::ChoosePirate, &Event::PirateChoice(c)) => {
                 if c >= self.pirate_cards.len() {
-                    return Err("Invalid pirate card".to_string());
+                    return Err(GameError::InvalidIndex(c));
                 }
                 let card = self.pirate_cards.swap_remove(c);
                 self.state = State::Fighting(card, 0, vec![], vec![], Using::None);
             }
-            (
-                State::Fighting(c, ref mut used_free, ref mut left, ref mut right, using),
-                Event::Fight,
-            ) => {
-                let has_stop = |c: &Card<'a>| {
-                    !c.destroy && c.description.kind.to_fighting_card().capacity == Capacity::Stop
-                };
-
-                let free = if left.iter().any(has_stop) {
-                    false
-                } else {
-                    *used_free < self.free_cards(&c.description.kind.to_hazard_card())
-                };
-
+            // the cards drawn to sort must be put back first
+            (State::Fighting(_, _, _, _, Using::Sort(v)), e)
+                if !v.is_empty() && !matches!(e, Event::Continue | Event::Sort(..)) =>
+            {
+                return Err(GameError::UnexpectedEvent(e.clone()));
+            }
+            (State::Fighting(..), &Event::Fight) => {
+                let free = self.free_draws_left() > 0;
                 let card = self.fighting_deck_pop(free)?;
-                if free {
-                    left.push(card);
-                    *used_free += 1;
-                } else {
-                    right.push(card);
+                if let State::Fighting(_, used_free, left, right, using) = &mut self.state {
+                    if free {
+                        left.push(card);
+                        *used_free += 1;
+                    } else {
+                        right.push(card);
+                    }
+                    *using = Using::None;
                 }
-
-                *using = Using::None;
-                self.state = state;
             }
-            (
-                State::Fighting(_, _, ref mut left, ref mut right, using @ Using::None),
-                Event::Use(c),
-            ) => {
-                let card = left.iter_mut().chain(right.iter_mut()).nth(c);
-                if let Some(card) = card {
-                    if card.tapped {
-                        return Err("Card already used".to_string());
-                    }
-                    if card.destroy {
-                        return Err("Card is to be destroyed".to_string());
+            (State::Fighting(_, _, left, right, Using::None), &Event::Use(h)) => {
+                let c = position(left, right, h)?;
+                let card = left.iter_mut().chain(right.iter_mut()).nth(c).unwrap();
+                if card.tapped {
+                    return Err(GameError::CardAlreadyTapped(h));
+                }
+                if card.destroy {
+                    return Err(GameError::CardDestroyed(h));
+                }
+                if !card
+                    .description()
+                    .kind
+                    .to_fighting_card()
+                    .capacity
+                    .is_active(&self.card_set)
+                {
+                    return Err(GameError::NoCapacity(h));
+                }
+                card.tapped = true;
+                let res = self
+                    .with_fight(|game, using, left, right| game.use_card(h, using, left, right));
+                if let (Err(e), State::Fighting(_, _, left, right, _)) = (&res, &mut self.state) {
+                    let card = left
+                        .iter_mut()
+                        .chain(right.iter_mut())
+                        .find(|c| c.handle == h);
+                    if let (false, Some(card)) = (e.is_game_over(), card) {
+                        card.tapped = false;
                     }
-                    card.tapped = true;
-                    self.use_card(c, using, left, right)?;
-                } else {
-                    return Err("Invalid card".to_string());
                 }
-                self.state = state;
+                res?;
             }
-            (State::Fighting(_, _, _, ref mut right, using @ Using::Draw(_)), Event::Continue) => {
-                if let Using::Draw(n) = *using {
-                    self.use_draw_card(using, n, right)?;
-                    self.state = state;
-                }
+            (State::Fighting(_, _, _, _, Using::Draw(_)), &Event::Continue) => {
+                self.with_fight(|game, using, _, right| match *using {
+                    Using::Draw(n) => game.use_draw_card(using, n, right),
+                    _ => unreachable!(),
+                })?;
             }
-            (State::Fighting(_, _, _, _, using), Event::Break) => {
+            (State::Fighting(_, _, _, _, using), &Event::Break) => {
                 *using = Using::None;
-                self.state = state;
             }
-            (State::Fighting(_, _, left, right, using @ Using::Destroy(_)), Event::Choice(c)) => {
+            (State::Fighting(_, _, left, right, using @ Using::Destroy(_)), &Event::Choice(h)) => {
                 if let Using::Destroy(d) = *using {
-                    if c == d {
-                        return Err("You can't destroy the current card".to_string());
+                    if h == d {
+                        return Err(GameError::SelfTarget(h));
                     }
                 }
-                let card = left.iter_mut().chain(right.iter_mut()).nth(c); // FIXME: make me lambda
-                if let Some(card) = card {
-                    if card.destroy {
-                        return Err("Card already destroyed".to_string());
-                    }
-                    card.destroy = true;
-                    *using = Using::None;
-                    self.state = state;
+                let c = position(left, right, h)?;
+                let card = left.iter_mut().chain(right.iter_mut()).nth(c).unwrap();
+                if card.destroy {
+                    return Err(GameError::CardDestroyed(h));
                 }
+                card.destroy = true;
+                *using = Using::None;
             }
-            (State::Fighting(_, _, left, right, using @ Using::Double), Event::Choice(c)) => {
-                let card = left.iter_mut().chain(right.iter_mut()).nth(c);
-                if let Some(card) = card {
-                    if card.destroy {
-                        return Err("Card is to be destroyed".to_string());
-                    }
-                    if card.double {
-                        return Err("Card already doubled".to_string());
-                    }
-                    card.double = true;
-                    *using = Using::None;
-                    self.state = state;
+            (State::Fighting(_, _, left, right, using @ Using::Double), &Event::Choice(h)) => {
+                let c = position(left, right, h)?;
+                let card = left.iter_mut().chain(right.iter_mut()).nth(c).unwrap();
+                if card.destroy {
+                    return Err(GameError::CardDestroyed(h));
                 }
+                if card.double {
+                    return Err(GameError::CardAlreadyDoubled(h));
+                }
+                card.double = true;
+                *using = Using::None;
             }
-            (State::Fighting(_, _, left, right, using @ Using::Copy), Event::Choice(c)) => {
-                let card = left.iter().chain(right.iter()).nth(c);
-                if let Some(card) = card {
-                    if card.destroy {
-                        return Err("Card is to be destroyed".to_string());
-                    }
+            (State::Fighting(_, _, left, right, Using::Copy), &Event::Choice(h)) => {
+                let c = position(left, right, h)?;
+                if left.iter().chain(right.iter()).nth(c).unwrap().destroy {
+                    return Err(GameError::CardDestroyed(h));
+                }
+                let res = self.with_fight(|game, using, left, right| {
                     *using = Using::None;
-                    self.use_card(c, using, left, right)?;
-                    self.state = state;
+                    game.use_card(h, using, left, right)
+                });
+                if let (Err(e), State::Fighting(_, _, _, _, using)) = (&res, &mut self.state) {
+                    if !e.is_game_over() {
+                        *using = Using::Copy;
+                    }
                 }
+                res?;
             }
-            (State::Fighting(_, _, _, _, using @ Using::Sort(_)), Event::Continue) => {
-                self.use_sort(using)?;
-                self.state = state;
+            (State::Fighting(_, _, _, _, Using::Sort(_)), &Event::Continue) => {
+                self.with_fight(|game, using, _, _| game.use_sort(using))?;
             }
-            (State::Fighting(_, _, left, right, using @ Using::Swap(_, _)), Event::Choice(c)) => {
-                if let Using::Swap(swap_card, n) = using {
-                    if c == *swap_card {
-                        return Err("You can't swap the current card".to_string());
-                    }
-
-                    let card = left.iter_mut().chain(right.iter_mut()).nth(c);
-                    if let Some(card) = card {
-                        if card.destroy {
-                            return Err("Card is to be destroyed".to_string());
-                        }
-
-                        self.fighting_discard.push(card.clone());
-                        *card = self.fighting_deck_pop(true)?;
+            (State::Fighting(_, _, left, right, Using::Swap(swap_card, _)), &Event::Choice(h)) => {
+                if h == *swap_card {
+                    return Err(GameError::SelfTarget(h));
+                }
+                let c = position(left, right, h)?;
+                let card = left.iter().chain(right.iter()).nth(c).unwrap();
+                if card.destroy {
+                    return Err(GameError::CardDestroyed(h));
+                }
 
+                // discarded first, to be reshuffled if the deck is empty
+                self.fighting_discard.push(card.clone());
+                let new = self.fighting_deck_pop(true)?;
+                if let State::Fighting(_, _, left, right, using) = &mut self.state {
+                    *left.iter_mut().chain(right.iter_mut()).nth(c).unwrap() = new;
+                    if let Using::Swap(_, n) = using {
                         *n -= 1;
                         if *n == 0 {
                             *using = Using::None;
                         }
-                        self.state = state;
                     }
                 }
             }
             (
-                State::Fighting(_, _, left, right, using @ Using::UnderDeck(_)),
-                Event::ChoiceUnder(mut c, replace),
+                State::Fighting(_, _, left, right, Using::UnderDeck(d)),
+                &Event::ChoiceUnder(h, replace),
             ) => {
-                if let Using::UnderDeck(d) = *using {
-                    if c == d {
-                        return Err("You can't swap the current card".to_string());
-                    }
+                if h == *d {
+                    return Err(GameError::SelfTarget(h));
                 }
+                let mut c = position(left, right, h)?;
                 if c >= left.len() && replace {
-                    return Err("Can't replace a card from the right pile".to_string());
+                    return Err(GameError::CannotReplace(h));
                 }
 
                 let mut pile = left;
                 if c >= pile.len() {
                     c -= pile.len();
-                    if c >= right.len() {
-                        return Err("Invalid card".to_string());
-                    }
                     pile = right;
                 }
                 if pile[c].destroy {
-                    return Err("Card is already detroyed".to_string());
+                    return Err(GameError::CardDestroyed(h));
                 }
                 let mut card = pile.remove(c);
                 card.reset();
-                self.fighting_deck.insert(0, card.clone());
+                self.fighting_deck.insert(0, card);
+                self.known_bottom += 1;
 
                 if replace {
-                    pile.insert(c, self.fighting_deck_pop(true)?);
+                    let new = self.fighting_deck_pop(true)?;
+                    if let State::Fighting(_, _, left, _, _) = &mut self.state {
+                        left.insert(c, new);
+                    }
+                }
+                if let State::Fighting(_, _, _, _, using) = &mut self.state {
+                    *using = Using::None;
                 }
-
-                *using = Using::None;
-                self.state = state;
             }
-            (
-                State::Fighting(_, _, _, _, using @ Using::Sort(_)),
-                Event::Sort(ref mut order, destroy),
-            ) => {
-                match using {
-                    Using::Sort(v) => {
-                        let mut check = order.clone();
-                        check.sort();
-                        check.dedup();
-                        let mut order = &order[..];
-                        if v.len() != check.len() {
-                            return Err("Invalid sort".to_string());
-                        }
-                        if destroy {
-                            if let Some(c) = v.get(order[0]) {
-                                self.destroyed.push(c.clone());
-                            } else {
-                                return Err("Invalid sort".to_string());
-                            }
-                            order = &order[1..];
-                        }
-                        for i in order {
-                            if let Some(c) = v.get(*i) {
-                                self.fighting_deck.push(c.clone());
-                            } else {
-                                return Err("Invalid sort".to_string());
-                            }
-                        }
+            (State::Fighting(_, _, _, _, using @ Using::Sort(_)), Event::Sort(order, destroy)) => {
+                if let Using::Sort(v) = using {
+                    if order.len() != v.len()
+                        || (0..v.len()).any(|i| !order.contains(&i))
+                        || (*destroy && order.is_empty())
+                    {
+                        return Err(GameError::InvalidSort(order.clone()));
+                    }
+                    let mut order = &order[..];
+                    if *destroy {
+                        self.effects.push(Effect::CardDestroyed(v[order[0]].id));
+                        self.destroyed.push(v[order[0]].clone());
+                        order = &order[1..];
                     }
-                    _ => panic!("Invalid sort state"),
+                    for i in order {
+                        self.fighting_deck.push(v[*i].clone());
+                    }
+                    self.known_top += order.len();
                 }
                 *using = Using::None;
-                self.state = state;
             }
-            (State::Fighting(ref c, used_free, ref mut left, ref mut right, _), Event::Win) => {
+            (State::Fighting(_, used_free, _, _, _), &Event::Win) => {
                 if *used_free == 0 {
-                    return Err("You must draw at least one card".to_string());
+                    return Err(GameError::MustDrawFirst);
                 }
-                if self.fight_diff().unwrap_or(-1) < 0 {
-                    return Err("It's not possible to win!".to_string());
+                let diff = self.fight_diff().unwrap_or(-1);
+                if diff < 0 {
+                    return Err(GameError::CannotWin(diff));
                 }
-                if c.description.is_pirate() {
-                    self.pirate_cards_won.push(c.clone());
+
+                let (c, mut cards, mut right) = self.take_fight();
+                self.effects.push(Effect::FightWon(c.id));
+                if c.description().is_pirate() {
+                    self.pirate_cards_won.push(c);
                 } else {
-                    self.fighting_discard.push(c.clone());
+                    self.fighting_discard.push(c);
                 }
-                let cards = [&left[..], &right[..]].concat();
-                return self.end_fight(&cards);
+                cards.append(&mut right);
+                return self.end_fight(cards);
             }
-            (
-                State::Fighting(ref c, used_free, ref left, ref right, _),
-                Event::Lose(ref mut discard),
-            ) => {
-                if c.description.is_pirate() {
-                    return Err("Arrr, you can't decide to lose against a pirate!".to_string());
+            (State::Fighting(c, used_free, left, right, _), Event::Lose(discard)) => {
+                let id = c.id;
+                if c.description().is_pirate() {
+                    return Err(GameError::CannotLoseToPirate);
                 }
                 if *used_free == 0 {
-                    return Err("You must first play a card".to_string());
+                    return Err(GameError::MustDrawFirst);
+                }
+                for h in discard.iter() {
+                    position(left, right, *h)?;
                 }
-                discard.sort_unstable_by(|a, b| b.cmp(a));
-                let mut discard = discard.to_vec();
+                // the last cards first, swap_remove() moves the last one in their place
+                let mut discard = discard.clone();
+                discard.sort_unstable_by_key(|h| Reverse(position(left, right, *h).unwrap()));
                 discard.dedup();
                 let mut cost = 0;
-                let mut concat = [&left[..], &right[..]].concat();
-                for i in discard.iter() {
-                    if let Some(d) = concat.get(*i) {
-                        cost += d.description.get_destroy_value().unwrap();
-                    } else {
-                        return Err("Invalid discard #".to_string());
-                    }
+                for h in discard.iter() {
+                    let i = position(left, right, *h).unwrap();
+                    let card = left.iter().chain(right.iter()).nth(i).unwrap();
+                    cost += card.description().get_destroy_value().unwrap();
                 }
                 let diff = self.fight_diff().unwrap();
                 if cost > diff.abs() {
-                    return Err(format!(
-                        "Not enough diff:{} for this much discard:{}",
-                        diff, cost
-                    ));
-                }
-                self.modify_life(diff)?;
-                self.hazard_discard.push(c.clone());
-                for i in discard.iter() {
-                    let destroyed = concat.swap_remove(*i);
+                    return Err(GameError::DiscardTooCostly { cost, diff });
+                }
+
+                self.effects.push(Effect::FightLost(id));
+                self.modify_life(diff, LifeReason::FightLost)?;
+                let (c, mut cards, mut right) = self.take_fight();
+                self.hazard_discard.push(c);
+                cards.append(&mut right);
+                for h in discard.iter() {
+                    let i = position(&cards, &[], *h).unwrap();
+                    let destroyed = cards.swap_remove(i);
+                    self.effects.push(Effect::CardDestroyed(destroyed.id));
                     self.destroyed.push(destroyed);
                 }
-                return self.end_fight(&concat);
+                return self.end_fight(cards);
             }
-            (s, e) => {
-                return Err(format!("Wrong state, event combination: {:#?} {:#?}", s, e).to_string());
+            (_, e) => {
+                return Err(GameError::UnexpectedEvent(e.clone()));
             }
         }
         Ok(())
     }
 
+    /// Runs `f` with the cards of the fight and the capacity being used, out
+    /// of the state, so that it can change the game too.
+    ///
+    /// If the game ends meanwhile, the cards go to the discard pile.
+    fn with_fight<F>(&mut self, f: F) -> Result<(), GameError>
+    where
+        F: FnOnce(&mut Self, &mut Using, &mut Vec<Card>, &mut Vec<Card>) -> Result<(), GameError>,
+    {
+        let (mut using, mut left, mut right) = match &mut self.state {
+            State::Fighting(_, _, left, right, using) => (
+                mem::replace(using, Using::None),
+                mem::take(left),
+                mem::take(right),
+            ),
+            _ => panic!("Not fighting"),
+        };
+        let res = f(self, &mut using, &mut left, &mut right);
+        match &mut self.state {
+            State::Fighting(_, _, l, r, u) => {
+                *l = left;
+                *r = right;
+                *u = using;
+            }
+            _ => {
+                self.fighting_discard.append(&mut left);
+                self.fighting_discard.append(&mut right);
+            }
+        }
+        res
+    }
+
+    /// Ends the fight state: returns the hazard, and the left and right cards.
+    fn take_fight(&mut self) -> (Card, Vec<Card>, Vec<Card>) {
+        match mem::replace(&mut self.state, State::None) {
+            State::Fighting(c, _, left, right, _) => (c, left, right),
+            _ => panic!("Not fighting"),
+        }
+    }
+
     fn end_game(&mut self, won: bool) {
         if let Some(left) = self.get_left() {
             self.fighting_discard.extend(left.clone());
@@ -510,13 +891,14 @@ impl<'a> Friday<'a> {
             self.fighting_discard.extend(right.clone());
         }
         self.state = State::Ended(won);
+        self.effects.push(Effect::GameEnded { won });
     }
 
     pub fn score(&self) -> isize {
         let mut score: isize = self
             .fighting_discard
             .iter()
-            .map(|c| match &c.description.kind {
+            .map(|c| match &c.description().kind {
                 CardKind::Starting(card) => card.fighting_value,
                 CardKind::HazardKnowledge(_, knowledge) => knowledge.fighting_value,
                 CardKind::AgingNormal(_) | CardKind::AgingDifficult(_) => -5,
@@ -536,14 +918,14 @@ impl<'a> Friday<'a> {
         score
     }
 
-    pub fn get_left(&self) -> Option<&Vec<Card<'a>>> {
+    pub fn get_left(&self) -> Option<&Vec<Card>> {
         match &self.state {
             State::Fighting(_, _, left, _, _) => Some(left),
             _ => None,
         }
     }
 
-    pub fn get_right(&self) -> Option<&Vec<Card<'a>>> {
+    pub fn get_right(&self) -> Option<&Vec<Card>> {
         match &self.state {
             State::Fighting(_, _, _, right, _) => Some(right),
             _ => None,
@@ -552,69 +934,33 @@ impl<'a> Friday<'a> {
 
     fn get_fight_value(&self) -> Option<isize> {
         match &self.state {
-            State::Fighting(_, _, left, right, _) => {
-                let mut cards = left.iter().chain(right.iter()).collect::<Vec<_>>();
-                let maxzero = cards
-                    .iter()
-                    .filter(|c| {
-                        !c.destroy
-                            && c.description.kind.to_fighting_card().capacity == Capacity::MaxZero
-                    })
-                    .count();
-
-                if let State::Fighting(c, _, _, _, _) = &self.state {
-                    if let Hazard::PirateHalf(_) = c.description.kind.to_hazard_card().hazard {
-                        // FIXME: like the rest, untested & buggy ;)
-                        let half = (cards.len() + 1) / 2;
-                        cards.sort_unstable_by(|a, b| {
-                            if b.description.is_aging_normal() || b.description.is_aging_difficult()
-                            {
-                                return Ordering::Less;
-                            } else {
-                                b.get_fighting_value().cmp(&a.get_fighting_value())
-                            }
-                        });
-                        cards = cards[..half].to_vec();
-                    }
-                }
+            State::Fighting(c, _, left, right, _) => {
+                let hazard = &c.description().kind.to_hazard_card().hazard;
+                let value = hazard.with_rule(&self.card_set, |rule| {
+                    let mut cards: Vec<&Card> = left
+                        .iter()
+                        .chain(right.iter())
+                        .filter(|c| !c.destroy)
+                        .collect();
+                    rule.on_fight_cards(&mut cards);
 
-                let mut vals = cards
-                    .iter()
-                    .map(|c| c.get_fighting_value())
-                    .collect::<Vec<_>>();
-                vals.sort_unstable();
-
-                let mut val = vals[..vals.len() - maxzero].iter().sum();
-
-                if let State::Fighting(c, _, left, right, _) = &self.state {
-                    if let Hazard::PirateAdd(_) = c.description.kind.to_hazard_card().hazard {
-                        val += left.len() as isize + right.len() as isize;
+                    let mut vals: Vec<_> = cards.iter().map(|c| c.get_fighting_value()).collect();
+                    vals.sort_unstable();
+                    for c in cards.iter() {
+                        let capacity = &c.description().kind.to_fighting_card().capacity;
+                        capacity.with_effect(&self.card_set, |e| e.on_fight_value(&mut vals));
                     }
-                }
-                Some(val)
+
+                    rule.on_fight_value(&cards, vals.iter().sum())
+                });
+                Some(value)
             }
             _ => None,
         }
     }
 
-    fn hazard_level(&self, hazard: &Hazard, step: Step) -> u8 {
-        match *hazard {
-            Hazard::Leveled(l) => l[step as usize],
-            Hazard::Pirate(l) => l,
-            Hazard::PirateTwiceLife(l) => l,
-            Hazard::PirateAging => {
-                let n_aging = if self.level <= 2 { 10 } else { 11 };
-                (n_aging - self.aging_deck.len()) as u8 * 2
-            }
-            Hazard::PirateHalf(l) => l,
-            Hazard::PirateHazard => self
-                .hazard_deck
-                .iter()
-                .chain(self.hazard_discard.iter())
-                .map(|c| self.hazard_level(&c.description.kind.to_hazard_card().hazard, Step::Red))
-                .sum(),
-            Hazard::PirateAdd(l) => l,
-        }
+    pub fn hazard_level(&self, hazard: &Hazard, step: Step) -> u8 {
+        hazard.with_rule(&self.card_set, |r| r.objective(self, step))
     }
 
     pub fn fight_diff(&self) -> Option<isize> {
@@ -626,110 +972,132 @@ impl<'a> Friday<'a> {
                     step = step.prev();
                 }
                 let objective =
-                    self.hazard_level(&c.description.kind.to_hazard_card().hazard, step) as isize;
+                    self.hazard_level(&c.description().kind.to_hazard_card().hazard, step) as isize;
                 Some(now - objective)
             }
             _ => None,
         }
     }
 
-    fn hazard_pop(&mut self) -> Option<Vec<Card<'a>>> {
+    fn hazard_pop(&mut self) -> Option<Vec<Card>> {
         if self.hazard_deck.is_empty() {
             self.step = self.step.next();
             self.hazard_deck.append(&mut self.hazard_discard);
-            shuffle(&mut self.hazard_deck);
+            self.rng.shuffle(&mut self.hazard_deck);
+            self.effects.push(Effect::StepAdvanced(self.step));
         }
         if self.step == Step::Pirate {
             return None;
         }
         let n = self.hazard_deck.len().saturating_sub(2);
-        Some(self.hazard_deck.split_off(n))
+        let cards = self.hazard_deck.split_off(n);
+        self.effects
+            .push(Effect::HazardsDrawn(cards.iter().map(|c| c.id).collect()));
+        Some(cards)
     }
 
     fn aging(&mut self) {
         self.fighting_deck.append(&mut self.fighting_discard);
         if let Some(add_aging) = self.aging_deck.pop() {
+            self.effects.push(Effect::AgingAdded(add_aging.id));
             self.fighting_deck.push(add_aging);
         }
         for c in &mut self.fighting_deck {
             c.reset();
         }
-        shuffle(&mut self.fighting_deck);
+        self.rng.shuffle(&mut self.fighting_deck);
+        self.known_top = 0;
+        self.known_bottom = 0;
+        self.effects.push(Effect::DeckReshuffled);
     }
 
-    fn fighting_deck_pop(&mut self, free: bool) -> Result<Card<'a>, String> {
-        let twice = if let State::Fighting(c, _, _, _, _) = &self.state {
-            if let Hazard::PirateTwiceLife(_) = c.description.kind.to_hazard_card().hazard {
-                true
-            } else {
-                false
-            }
-        } else {
-            false
-        };
-
-        let cost = if twice { -2 } else { -1 };
+    fn fighting_deck_pop(&mut self, free: bool) -> Result<Card, GameError> {
         if !free {
-            self.modify_life(cost)?;
+            let cost = self.draw_cost();
+            self.modify_life(-cost, LifeReason::DrawCost)?;
         }
         if self.fighting_deck.is_empty() {
             self.aging();
         }
         if self.fighting_deck.is_empty() {
             self.end_game(false);
-            return Err("Fighting deck is empty".to_string());
+            return Err(GameError::DeckExhausted);
         }
-        Ok(self.fighting_deck.pop().unwrap())
+        let mut card = self.fighting_deck.pop().unwrap();
+        self.drawn += 1;
+        card.handle = Handle(self.drawn);
+        self.effects.push(Effect::CardDrawn(card.id));
+        self.known_top = self.known_top.saturating_sub(1);
+        self.known_bottom = self.known_bottom.min(self.fighting_deck.len());
+        Ok(card)
     }
 
-    fn take_two_pirate_cards() -> Vec<Card<'a>> {
-        let mut pirates: Vec<_> = CARDS.iter().filter(|c| c.is_pirate()).collect();
-        rand::thread_rng().shuffle(&mut pirates);
-        pirates[..2].iter().map(|c| Card::new(c)).collect()
+    /// The cards of the fighting deck whose place is known to the player: on
+    /// top and at the bottom, both given bottom to top.
+    pub(crate) fn known_fighting_cards(&self) -> (&[Card], &[Card]) {
+        let len = self.fighting_deck.len();
+        let bottom = self.known_bottom.min(len);
+        let top = self.known_top.min(len - bottom);
+        (
+            &self.fighting_deck[len - top..],
+            &self.fighting_deck[..bottom],
+        )
     }
 
-    fn make_aging_deck(level: Level) -> Vec<Card<'a>> {
-        let mut deck: Vec<_> = CARDS
-            .iter()
-            .filter(|c| c.is_aging_difficult())
-            .flat_map(|c| iter::repeat(c).take(c.start_qty))
-            .map(|c| Card::new(c))
-            .collect();
-        shuffle(&mut deck);
-        let mut normal: Vec<_> = CARDS
-            .iter()
-            .filter(|c| c.is_aging_normal())
-            .filter(|c| match level {
-                1 | 2 => !c.is_very_stupid(),
-                _ => true,
-            })
-            .flat_map(|c| iter::repeat(c).take(c.start_qty))
-            .map(|c| Card::new(c))
-            .collect();
-        shuffle(&mut normal);
+    /// A copy of the game where the hidden order of the decks is dealt again
+    /// from `seed`, as the player could imagine it.
+    ///
+    /// The copy has no history, and its future shuffles derive from `seed` too.
+    pub(crate) fn hypothetical(&self, seed: u64) -> Self {
+        let mut game = self.clone();
+        game.log.clear();
+        game.redo.clear();
+        game.start = None;
+        game.rng = Shuffler::new(seed);
+
+        let len = game.fighting_deck.len();
+        let bottom = game.known_bottom.min(len);
+        let top = game.known_top.min(len - bottom);
+        game.rng.shuffle(&mut game.fighting_deck[bottom..len - top]);
+
+        // aging difficult cards stay at the bottom
+        let (mut aging, mut normal): (Vec<_>, Vec<_>) = game
+            .aging_deck
+            .drain(..)
+            .partition(|c| c.description().is_aging_difficult());
+        game.rng.shuffle(&mut aging);
+        game.rng.shuffle(&mut normal);
+        aging.append(&mut normal);
+        game.aging_deck = aging;
+
+        game.rng.shuffle(&mut game.hazard_deck);
+        game
+    }
+
+    fn take_pirate_cards(set: &CardSet, n: usize, rng: &mut Shuffler) -> Vec<Card> {
+        let mut pirates: Vec<_> = set.ids(|c| c.is_pirate()).collect();
+        rng.shuffle(&mut pirates);
+        pirates[..n].iter().map(|id| set.card(*id)).collect()
+    }
+
+    fn make_aging_deck(set: &CardSet, rules: &RuleSet, rng: &mut Shuffler) -> Vec<Card> {
+        let mut deck = deal_cards(set, |c| c.is_aging_difficult());
+        rng.shuffle(&mut deck);
+        let mut normal = deal_cards(set, |c| c.is_aging_normal() && is_aging_at(c, rules));
+        rng.shuffle(&mut normal);
         deck.append(&mut normal);
         deck
     }
 
-    fn make_fighting_deck() -> Vec<Card<'a>> {
-        let mut start_cards: Vec<_> = CARDS
-            .iter()
-            .filter(|c| c.is_starting())
-            .flat_map(|c| iter::repeat(c).take(c.start_qty))
-            .map(|c| Card::new(c))
-            .collect();
-        shuffle(&mut start_cards);
+    fn make_fighting_deck(set: &CardSet, rng: &mut Shuffler) -> Vec<Card> {
+        let mut start_cards = deal_cards(set, |c| c.is_starting());
+        rng.shuffle(&mut start_cards);
         start_cards
     }
 
-    fn make_hazard_deck() -> Vec<Card<'a>> {
-        let mut hazard: Vec<_> = CARDS
-            .iter()
-            .filter(|c| c.is_hazard_knowledge())
-            .flat_map(|c| iter::repeat(c).take(c.start_qty))
-            .map(|c| Card::new(c))
-            .collect();
-        shuffle(&mut hazard);
+    fn make_hazard_deck(set: &CardSet, rng: &mut Shuffler) -> Vec<Card> {
+        let mut hazard = deal_cards(set, |c| c.is_hazard_knowledge());
+        rng.shuffle(&mut hazard);
         hazard
     }
 }
@@ -738,9 +1106,11 @@ impl<'a> Friday<'a> {
 mod tests {
     use super::*;
 
+    use crate::rules::RuleError;
+
     #[test]
     fn test_init() {
-        let mut game = Friday::new(1);
+        let mut game = Friday::new(1).unwrap();
 
         assert_eq!(game.aging_deck.len(), 10);
         assert_eq!(game.fighting_deck.len(), 18);
@@ -750,25 +1120,129 @@ mod tests {
         game.next(Event::HazardChoice(Some(0))).unwrap();
         game.next(Event::Fight).unwrap();
 
-        let game = Friday::new(2);
+        let game = Friday::new(2).unwrap();
 
         assert_eq!(game.aging_deck.len(), 9);
         assert_eq!(game.fighting_deck.len(), 19);
         assert_eq!(game.hazard_deck.len(), 28);
         assert_eq!(game.pirate_cards.len(), 2);
 
-        let game = Friday::new(3);
+        let game = Friday::new(3).unwrap();
 
         assert_eq!(game.aging_deck.len(), 10);
         assert_eq!(game.fighting_deck.len(), 19);
         assert_eq!(game.hazard_deck.len(), 28);
         assert_eq!(game.pirate_cards.len(), 2);
 
-        let game = Friday::new(4);
+        let game = Friday::new(4).unwrap();
 
         assert_eq!(game.aging_deck.len(), 10);
         assert_eq!(game.fighting_deck.len(), 19);
         assert_eq!(game.hazard_deck.len(), 28);
         assert_eq!(game.pirate_cards.len(), 2);
     }
+
+    #[test]
+    fn test_seed() {
+        let play = |seed| {
+            let mut game = Friday::with_seed(3, seed).unwrap();
+            game.next(Event::HazardChoice(Some(1))).unwrap();
+            for _ in 0..4 {
+                game.next(Event::Fight).unwrap();
+            }
+            format!("{:?}", game)
+        };
+
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(42), play(43));
+        assert_eq!(Friday::with_seed(1, 7).unwrap().seed(), 7);
+        assert_eq!(
+            Friday::with_seed(5, 7).unwrap_err(),
+            GameError::Rules(RuleError::InvalidLevel(5))
+        );
+        let mut rules = RuleSet::level(1).unwrap();
+        rules.pirates = 0;
+        assert!(matches!(
+            Friday::with_rules(rules, 7, CardSet::builtin()),
+            Err(GameError::Rules(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay() {
+        // house rules are played back too
+        let mut rules = RuleSet::level(2).unwrap();
+        rules.life_points = 12;
+        rules.extra_aging = 3;
+        let mut game = Friday::with_rules(rules, 1234, CardSet::builtin()).unwrap();
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        game.next(Event::Fight).unwrap();
+        assert_eq!(
+            game.next(Event::Choice(Handle(5))),
+            Err(GameError::UnexpectedEvent(Event::Choice(Handle(5))))
+        );
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Fight).unwrap();
+        if game.next(Event::Win).is_err() {
+            game.next(Event::Lose(vec![])).unwrap();
+        }
+        game.next(Event::HazardChoice(Some(1))).unwrap();
+        assert_eq!(game.events().len(), 6);
+
+        let replayed = Friday::replay(
+            game.rules.clone(),
+            game.seed(),
+            game.card_set.clone(),
+            game.events(),
+        )
+        .unwrap();
+        assert_eq!(format!("{:?}", replayed), format!("{:?}", game));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let public = |game: &Friday| {
+            format!(
+                "{:?}",
+                (
+                    &game.state,
+                    &game.fighting_deck,
+                    &game.hazard_deck,
+                    game.life_points,
+                    game.events()
+                )
+            )
+        };
+        let mut game = Friday::with_seed(1, 5).unwrap();
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        let before = public(&game);
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Fight).unwrap();
+        let after = public(&game);
+
+        game.undo().unwrap();
+        game.undo().unwrap();
+        assert_eq!(public(&game), before);
+        game.redo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(public(&game), after);
+        assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+
+        game.undo().unwrap();
+        game.next(Event::Break).unwrap();
+        assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+    }
+
+    #[test]
+    fn test_owned() {
+        fn is_owned<T: Send + 'static>(_: T) {}
+        let game = Friday::with_seed(1, 5).unwrap();
+        is_owned(game.clone());
+        let handle = std::thread::spawn(move || game.score());
+        assert_eq!(
+            handle.join().unwrap(),
+            Friday::with_seed(1, 5).unwrap().score()
+        );
+    }
 }
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::cards::{Card, CardSet, Hazard, Step};
+use crate::friday::{is_aging_at, Friday};
+
+/// How a hazard or pirate card is fought.
+///
+/// Only the objective is required, the other hooks follow the usual rules
+/// by default.
+pub trait HazardRule: Send + Sync {
+    /// Fighting value to reach at `step`.
+    fn objective(&self, game: &Friday, step: Step) -> u8;
+
+    /// Number of cards drawn for free, `free_cards` being printed on the card.
+    fn free_cards(&self, _game: &Friday, free_cards: u8) -> u8 {
+        free_cards
+    }
+
+    /// Life points paid for each card drawn beyond the free ones.
+    fn draw_cost(&self) -> isize {
+        1
+    }
+
+    /// Keeps the cards that count in the fight, destroyed cards already left
+    /// out.
+    fn on_fight_cards(&self, _cards: &mut Vec<&Card>) {}
+
+    /// Changes the fighting value of the counted `cards`.
+    fn on_fight_value(&self, _cards: &[&Card], value: isize) -> isize {
+        value
+    }
+}
+
+struct Leveled([u8; 3]);
+
+impl HazardRule for Leveled {
+    fn objective(&self, _game: &Friday, step: Step) -> u8 {
+        self.0[step as usize]
+    }
+}
+
+struct Pirate(u8);
+
+impl HazardRule for Pirate {
+    fn objective(&self, _game: &Friday, _step: Step) -> u8 {
+        self.0
+    }
+}
+
+struct PirateTwiceLife(u8);
+
+impl HazardRule for PirateTwiceLife {
+    fn objective(&self, _game: &Friday, _step: Step) -> u8 {
+        self.0
+    }
+
+    fn draw_cost(&self) -> isize {
+        2
+    }
+}
+
+struct PirateAging;
+
+impl HazardRule for PirateAging {
+    fn objective(&self, game: &Friday, _step: Step) -> u8 {
+        let n_aging: usize = game
+            .card_set
+            .ids(|c| is_aging_at(c, &game.rules))
+            .map(|id| game.card_set[id].start_qty)
+            .sum();
+        let drawn = n_aging.saturating_sub(game.aging_deck.len());
+        (drawn * 2).min(u8::MAX as usize) as u8
+    }
+}
+
+struct PirateHalf(u8);
+
+impl HazardRule for PirateHalf {
+    fn objective(&self, _game: &Friday, _step: Step) -> u8 {
+        self.0
+    }
+
+    fn on_fight_cards(&self, cards: &mut Vec<&Card>) {
+        // aging cards must be part of the counted half, then the best ones
+        let half = cards.len().div_ceil(2);
+        cards.sort_by_key(|c| {
+            let aging = c.description().is_aging_normal() || c.description().is_aging_difficult();
+            (!aging, -c.get_fighting_value())
+        });
+        cards.truncate(half);
+    }
+}
+
+struct PirateHazard;
+
+impl PirateHazard {
+    fn hazards(game: &Friday) -> impl Iterator<Item = &Card> {
+        game.hazard_deck.iter().chain(game.hazard_discard.iter())
+    }
+}
+
+impl HazardRule for PirateHazard {
+    fn objective(&self, game: &Friday, _step: Step) -> u8 {
+        Self::hazards(game)
+            .map(|c| game.hazard_level(&c.description().kind.to_hazard_card().hazard, Step::Red))
+            .sum()
+    }
+
+    fn free_cards(&self, game: &Friday, _free_cards: u8) -> u8 {
+        Self::hazards(game)
+            .map(|c| c.description().kind.to_hazard_card().free_cards)
+            .sum()
+    }
+}
+
+struct PirateAdd(u8);
+
+impl HazardRule for PirateAdd {
+    fn objective(&self, _game: &Friday, _step: Step) -> u8 {
+        self.0
+    }
+
+    fn on_fight_value(&self, cards: &[&Card], value: isize) -> isize {
+        value + cards.len() as isize
+    }
+}
+
+/// Objective of a hazard whose rule was never registered.
+struct Unregistered;
+
+impl HazardRule for Unregistered {
+    fn objective(&self, _game: &Friday, _step: Step) -> u8 {
+        0
+    }
+}
+
+/// A hazard registered on a card set, known by its name in the cards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomHazard(Arc<str>);
+
+impl CustomHazard {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CustomHazard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl CardSet {
+    /// Gives `rule` to the cards of the set with the hazard
+    /// `{"Custom": name}`.
+    ///
+    /// Cards whose custom hazard was never registered have an objective of 0.
+    pub fn register_hazard(&mut self, name: &str, rule: Box<dyn HazardRule>) -> Hazard {
+        self.hazards.set(name, rule);
+        Hazard::Custom(CustomHazard(name.into()))
+    }
+}
+
+impl Hazard {
+    /// Calls `f` with the rule of the hazard, custom ones being looked up in
+    /// `set`.
+    pub fn with_rule<R, F>(&self, set: &CardSet, f: F) -> R
+    where
+        F: FnOnce(&dyn HazardRule) -> R,
+    {
+        match self {
+            Hazard::Leveled(l) => f(&Leveled(*l)),
+            Hazard::Pirate(l) => f(&Pirate(*l)),
+            Hazard::PirateTwiceLife(l) => f(&PirateTwiceLife(*l)),
+            Hazard::PirateAging => f(&PirateAging),
+            Hazard::PirateHalf(l) => f(&PirateHalf(*l)),
+            Hazard::PirateHazard => f(&PirateHazard),
+            Hazard::PirateAdd(l) => f(&PirateAdd(*l)),
+            Hazard::Custom(c) => f(set.hazards.get(c.name()).unwrap_or(&Unregistered)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::FridayBuilder;
+    use crate::friday::Event;
+
+    /// Objective of 3 per card in the fighting discard, no free card.
+    struct Hoarder;
+
+    impl HazardRule for Hoarder {
+        fn objective(&self, game: &Friday, _step: Step) -> u8 {
+            game.fighting_discard.len() as u8 * 3
+        }
+
+        fn free_cards(&self, _game: &Friday, _free_cards: u8) -> u8 {
+            0
+        }
+
+        fn on_fight_value(&self, _cards: &[&Card], value: isize) -> isize {
+            value * 2
+        }
+    }
+
+    #[test]
+    fn test_custom() {
+        let text = include_str!("../data/cards.json").replacen(
+            r#""PirateAging""#,
+            r#"{"Custom": "test-hoarder"}"#,
+            1,
+        );
+        let mut set = CardSet::from_json(text.as_bytes()).unwrap();
+        let hoarder = set
+            .ids(|c| c.is_pirate() && c.kind.to_hazard_card().hazard.to_string() == "test-hoarder")
+            .next()
+            .unwrap();
+        let focused = set
+            .ids(|c| c.is_starting() && c.kind.to_fighting_card().title == "focused")
+            .next()
+            .unwrap();
+        let hazard = set[hoarder].kind.to_hazard_card().hazard.clone();
+        assert!(hazard.is_special());
+
+        assert_eq!(
+            set.register_hazard("test-hoarder", Box::new(Hoarder)),
+            hazard
+        );
+        let mut game = FridayBuilder::new(1)
+            .card_set(Arc::new(set))
+            .life_points(10)
+            .fighting_deck(&[focused, focused])
+            .fighting_discard(&[focused, focused])
+            .fight(hoarder)
+            .build();
+        assert_eq!(game.free_draws_left(), 0);
+        assert_eq!(game.draw_cost(), 1);
+        game.next(Event::Fight).unwrap();
+        assert_eq!(game.life_points, 9);
+        assert_eq!(game.fight_diff(), Some(2 - 6));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::cards::{Card, CardSet};
+use crate::friday::{Event, Friday, State, Using};
+use crate::view::PlayerView;
+
+/// Returns every permutation of `0..n`.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut perms = vec![];
+    for p in permutations(n - 1) {
+        for i in 0..n {
+            let mut p = p.clone();
+            p.insert(i, n - 1);
+            perms.push(p);
+        }
+    }
+    perms
+}
+
+impl Friday {
+    /// Lists the events that `next()` accepts in the current state.
+    ///
+    /// Cards already marked to be destroyed are not offered as targets. A
+    /// lost fight is listed once, as `Lose` with no discard: any discard
+    /// whose destroy value fits in `discard_budget()` is accepted too.
+    pub fn legal_events(&self) -> Vec<Event> {
+        legal_events(
+            &self.state,
+            &self.card_set,
+            self.pirate_cards.len(),
+            self.fight_diff(),
+        )
+    }
+
+    /// The destroy value the cards discarded with `Lose` may add up to, when
+    /// the fight can be lost.
+    pub fn discard_budget(&self) -> Option<isize> {
+        discard_budget(&self.state, self.fight_diff())
+    }
+}
+
+impl PlayerView {
+    /// Lists the events that the game accepts, as `Friday::legal_events()`.
+    pub fn legal_events(&self) -> Vec<Event> {
+        legal_events(
+            &self.state,
+            &self.card_set,
+            self.pirate_cards.len(),
+            self.fight_diff,
+        )
+    }
+
+    /// As `Friday::discard_budget()`.
+    pub fn discard_budget(&self) -> Option<isize> {
+        discard_budget(&self.state, self.fight_diff)
+    }
+}
+
+fn discard_budget(state: &State, fight_diff: Option<isize>) -> Option<isize> {
+    match state {
+        State::Fighting(c, used_free, _, _, using) => {
+            // the cards drawn to sort must be put back first
+            let sorting = matches!(using, Using::Sort(v) if !v.is_empty());
+            if *used_free > 0 && !sorting && !c.description().is_pirate() {
+                fight_diff.map(isize::abs)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The events accepted in `state`, played with `set`, with `pirates` left and
+/// the fight difference `fight_diff`.
+fn legal_events(
+    state: &State,
+    set: &CardSet,
+    pirates: usize,
+    fight_diff: Option<isize>,
+) -> Vec<Event> {
+    let mut events = vec![];
+    match state {
+        State::ChooseHazard(h) => {
+            events.extend((0..h.len()).map(|i| Event::HazardChoice(Some(i))));
+            if h.len() == 1 {
+                events.push(Event::HazardChoice(None));
+            }
+        }
+        State::ChoosePirate => {
+            events.extend((0..pirates).map(Event::PirateChoice));
+        }
+        State::Fighting(_, used_free, left, right, using) => {
+            let cards: Vec<&Card> = left.iter().chain(right.iter()).collect();
+            let targets = || cards.iter().enumerate().filter(|(_, c)| !c.destroy);
+            // the cards drawn to sort must be put back first
+            let sorting = matches!(using, Using::Sort(v) if !v.is_empty());
+
+            if !sorting {
+                events.push(Event::Fight);
+                events.push(Event::Break);
+            }
+            match using {
+                Using::None => events.extend(
+                    targets()
+                        .filter(|(_, c)| {
+                            !c.tapped
+                                && c.description()
+                                    .kind
+                                    .to_fighting_card()
+                                    .capacity
+                                    .is_active(set)
+                        })
+                        .map(|(_, c)| Event::Use(c.handle)),
+                ),
+                Using::Draw(_) => events.push(Event::Continue),
+                Using::Destroy(d) | Using::Swap(d, _) => events.extend(
+                    targets()
+                        .filter(|(_, c)| c.handle != *d)
+                        .map(|(_, c)| Event::Choice(c.handle)),
+                ),
+                Using::Double => events.extend(
+                    targets()
+                        .filter(|(_, c)| !c.double)
+                        .map(|(_, c)| Event::Choice(c.handle)),
+                ),
+                Using::Copy => events.extend(targets().map(|(_, c)| Event::Choice(c.handle))),
+                Using::Sort(v) => {
+                    if v.len() < 3 {
+                        events.push(Event::Continue);
+                    }
+                    for order in permutations(v.len()) {
+                        if !order.is_empty() {
+                            events.push(Event::Sort(order.clone(), true));
+                        }
+                        events.push(Event::Sort(order, false));
+                    }
+                }
+                Using::UnderDeck(d) => {
+                    for (i, c) in targets().filter(|(_, c)| c.handle != *d) {
+                        events.push(Event::ChoiceUnder(c.handle, false));
+                        if i < left.len() {
+                            events.push(Event::ChoiceUnder(c.handle, true));
+                        }
+                    }
+                }
+            }
+
+            if *used_free > 0 && !sorting && fight_diff.unwrap() >= 0 {
+                events.push(Event::Win);
+            }
+            if discard_budget(state, fight_diff).is_some() {
+                events.push(Event::Lose(vec![]));
+            }
+        }
+        State::None | State::Ended(_) => {}
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    use crate::cards::Handle;
+
+    fn accepts(game: &Friday, event: &Event) -> bool {
+        game.clone().next(event.clone()).is_ok()
+    }
+
+    fn candidates(game: &Friday) -> Vec<Event> {
+        let left = game.get_left().map_or(&[][..], |l| &l[..]);
+        let right = game.get_right().map_or(&[][..], |r| &r[..]);
+        // the cards of the fight, and some that are not or no more
+        let mut handles: Vec<_> = left.iter().chain(right.iter()).map(|c| c.handle).collect();
+        handles.extend(game.fighting_discard.iter().map(|c| c.handle));
+        handles.push(Handle(0));
+        handles.push(Handle(1000));
+        let mut events = vec![
+            Event::HazardChoice(None),
+            Event::Fight,
+            Event::Win,
+            Event::Continue,
+            Event::Break,
+            Event::Lose(vec![]),
+        ];
+        for i in 0..left.len() + right.len() + 2 {
+            events.push(Event::HazardChoice(Some(i)));
+            events.push(Event::PirateChoice(i));
+        }
+        for h in handles {
+            events.push(Event::Choice(h));
+            events.push(Event::Use(h));
+            events.push(Event::ChoiceUnder(h, false));
+            events.push(Event::ChoiceUnder(h, true));
+        }
+        events
+    }
+
+    #[test]
+    fn test_legal_events() {
+        for seed in 0..20 {
+            let mut rng = XorShiftRng::from_seed([seed as u32 + 1, 2, 3, 4]);
+            let mut game = Friday::with_seed(1 + seed as usize % 4, seed).unwrap();
+
+            while !game.is_ended() {
+                let legal = game.legal_events();
+                assert_eq!(game.player_view().legal_events(), legal);
+                for event in legal.iter() {
+                    assert!(accepts(&game, event), "{:?} rejected", event);
+                }
+                for event in candidates(&game) {
+                    assert_eq!(
+                        accepts(&game, &event),
+                        legal.contains(&event),
+                        "{:?}",
+                        event
+                    );
+                }
+
+                // a discard is accepted when it fits in the budget
+                let budget = game.discard_budget();
+                assert_eq!(game.player_view().discard_budget(), budget);
+                let left = game.get_left().map_or(&[][..], |l| &l[..]);
+                let right = game.get_right().map_or(&[][..], |r| &r[..]);
+                for c in left.iter().chain(right.iter()).filter(|c| !c.destroy) {
+                    let cost = c.description().get_destroy_value().unwrap();
+                    assert_eq!(
+                        accepts(&game, &Event::Lose(vec![c.handle])),
+                        budget.is_some_and(|b| cost <= b),
+                        "{:?}",
+                        c
+                    );
+                }
+
+                // Break alone never moves the game on
+                let moves: Vec<_> = legal.into_iter().filter(|e| *e != Event::Break).collect();
+                let event = rng.choose(&moves).unwrap().clone();
+                let _ = game.next(event);
+            }
+        }
+    }
+}
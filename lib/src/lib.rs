@@ -1,5 +1,24 @@
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
 
+pub mod advisor;
+pub mod builder;
+pub mod capacity;
 pub mod cards;
+pub mod discard;
+pub mod error;
+pub mod event;
 pub mod friday;
+pub mod hazard;
+mod legal;
+pub mod listener;
+pub mod odds;
+pub mod prompt;
+mod registry;
+pub mod rules;
+mod save;
+pub mod strategy;
+pub mod validate;
+pub mod view;
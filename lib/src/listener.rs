@@ -0,0 +1,233 @@
+use std::fmt;
+use std::mem;
+use std::sync::mpsc;
+
+use futures_channel::mpsc as async_mpsc;
+
+use crate::friday::{Effect, Event, Friday, State};
+
+/// Something following a game, like a logger or a statistics collector.
+///
+/// Both hooks do nothing by default.
+pub trait Listener: Send {
+    /// The game left the state `from` with `event`, and is now in its new
+    /// state.
+    ///
+    /// This is also called for the `Start` events the game plays by itself
+    /// once a fight is over.
+    fn on_transition(&mut self, _game: &Friday, _event: &Event, _from: &State) {}
+
+    /// `event` was played with `next()`, including the events that ended the
+    /// game.
+    fn on_event(&mut self, _game: &Friday, _event: &Event, _effects: &[Effect]) {}
+}
+
+impl<F> Listener for F
+where
+    F: FnMut(&Friday, &Event, &[Effect]) + Send,
+{
+    fn on_event(&mut self, game: &Friday, event: &Event, effects: &[Effect]) {
+        self(game, event, effects)
+    }
+}
+
+/// An event played with `next()`, as sent to channels.
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub event: Event,
+    pub effects: Vec<Effect>,
+    /// The state after the event.
+    pub state: State,
+}
+
+impl Update {
+    fn new(game: &Friday, event: &Event, effects: &[Effect]) -> Self {
+        Self {
+            event: event.clone(),
+            effects: effects.to_vec(),
+            state: game.state.clone(),
+        }
+    }
+}
+
+struct Channel(mpsc::Sender<Update>);
+
+impl Listener for Channel {
+    fn on_event(&mut self, game: &Friday, event: &Event, effects: &[Effect]) {
+        // the receiver may be gone, nobody is interested anymore
+        let _ = self.0.send(Update::new(game, event, effects));
+    }
+}
+
+struct StreamChannel(async_mpsc::UnboundedSender<Update>);
+
+impl Listener for StreamChannel {
+    fn on_event(&mut self, game: &Friday, event: &Event, effects: &[Effect]) {
+        let _ = self.0.unbounded_send(Update::new(game, event, effects));
+    }
+}
+
+/// Returned by `Friday::subscribe()`, to unsubscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(usize);
+
+/// The listeners of a game.
+///
+/// They are neither cloned nor saved with the game: copies made to look
+/// ahead, like the fight odds, don't notify anyone.
+#[derive(Default)]
+pub(crate) struct Listeners {
+    listeners: Vec<(ListenerId, Box<dyn Listener>)>,
+    next_id: usize,
+}
+
+impl Clone for Listeners {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Listeners({})", self.listeners.len())
+    }
+}
+
+impl Listeners {
+    pub fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+}
+
+impl Friday {
+    /// Adds `listener`, notified of the next events.
+    pub fn subscribe(&mut self, listener: Box<dyn Listener>) -> ListenerId {
+        let id = ListenerId(self.listeners.next_id);
+        self.listeners.next_id += 1;
+        self.listeners.listeners.push((id, listener));
+        id
+    }
+
+    /// Removes a listener, returns whether it was subscribed.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        let len = self.listeners.listeners.len();
+        self.listeners.listeners.retain(|(i, _)| *i != id);
+        self.listeners.listeners.len() != len
+    }
+
+    /// A channel receiving each event played with `next()`.
+    pub fn updates(&mut self) -> mpsc::Receiver<Update> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribe(Box::new(Channel(tx)));
+        rx
+    }
+
+    /// Like `updates()`, as a `Stream` for async frontends.
+    pub fn stream(&mut self) -> async_mpsc::UnboundedReceiver<Update> {
+        let (tx, rx) = async_mpsc::unbounded();
+        self.subscribe(Box::new(StreamChannel(tx)));
+        rx
+    }
+
+    pub(crate) fn notify_transition(&mut self, event: &Event, from: &State) {
+        let mut listeners = mem::take(&mut self.listeners);
+        for (_, l) in listeners.listeners.iter_mut() {
+            l.on_transition(self, event, from);
+        }
+        self.listeners = listeners;
+    }
+
+    /// Tells the listeners about the last event of the log, and its effects.
+    pub(crate) fn notify_event(&mut self) {
+        let mut listeners = mem::take(&mut self.listeners);
+        let event = self.events().last().unwrap();
+        for (_, l) in listeners.listeners.iter_mut() {
+            l.on_event(self, event, &self.effects);
+        }
+        self.listeners = listeners;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Transitions(Arc<Mutex<Vec<(Event, String)>>>);
+
+    impl Listener for Transitions {
+        fn on_transition(&mut self, game: &Friday, event: &Event, _from: &State) {
+            let state = format!("{:?}", game.state);
+            let state = state.split('(').next().unwrap().to_string();
+            self.0.lock().unwrap().push((event.clone(), state));
+        }
+    }
+
+    #[test]
+    fn test_listeners() {
+        let mut game = Friday::with_seed(1, 7).unwrap();
+        let transitions = Arc::new(Mutex::new(vec![]));
+        game.subscribe(Box::new(Transitions(transitions.clone())));
+        let count = Arc::new(Mutex::new(0));
+        let counter = count.clone();
+        let id = game.subscribe(Box::new(move |_: &Friday, _: &Event, _: &[Effect]| {
+            *counter.lock().unwrap() += 1;
+        }));
+        let updates = game.updates();
+        let mut stream = game.stream();
+
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        game.next(Event::Fight).unwrap();
+        // look-ahead copies are not followed
+        crate::odds::fight_odds(&game).unwrap();
+        while game.fight_diff().unwrap() < 0 && game.free_draws_left() > 0 {
+            game.next(Event::Fight).unwrap();
+        }
+        let end = if game.fight_diff().unwrap() >= 0 {
+            Event::Win
+        } else {
+            Event::Lose(vec![])
+        };
+        game.next(end.clone()).unwrap();
+
+        let transitions = transitions.lock().unwrap();
+        let n = transitions.len();
+        // the fight ends, then the game starts the next one by itself
+        assert_eq!(transitions[n - 2], (end, "None".to_string()));
+        assert_eq!(
+            transitions[n - 1],
+            (Event::Start, "ChooseHazard".to_string())
+        );
+        let events = game.events().len();
+        assert_eq!(n, events + 1);
+        assert_eq!(*count.lock().unwrap(), events);
+        let updates: Vec<_> = updates.try_iter().collect();
+        assert_eq!(updates.len(), events);
+        assert!(updates[0].effects.is_empty());
+        assert_eq!(stream.try_recv().unwrap().event, updates[0].event);
+
+        assert!(game.unsubscribe(id));
+        assert!(!game.unsubscribe(id));
+        assert!(game.clone().listeners.is_empty());
+    }
+
+    #[test]
+    fn test_listeners_undo() {
+        let mut game = Friday::with_seed(1, 7).unwrap();
+        let updates = game.updates();
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        game.next(Event::Fight).unwrap();
+        game.undo().unwrap();
+        // the replayed events are not told again
+        assert_eq!(updates.try_iter().count(), 2);
+
+        game.next(Event::Fight).unwrap();
+        assert_eq!(updates.try_recv().unwrap().event, Event::Fight);
+        game.undo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(updates.try_recv().unwrap().event, Event::Fight);
+        assert!(!game.listeners.is_empty());
+    }
+}
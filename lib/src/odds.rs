@@ -0,0 +1,113 @@
+use crate::friday::{Event, Friday, State, Using};
+
+/// Number of deals tried by `fight_odds()`.
+pub const SAMPLES: u64 = 1000;
+
+/// Chances of the current fight, drawing cards without using capacities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FightOdds {
+    /// Probability of winning with the free draws left.
+    pub win_free: f64,
+    /// Life points lost by giving up after the free draws, on average over
+    /// the fights not won by then.
+    pub life_lost: f64,
+    /// Probability of winning when paying life for more draws, until death.
+    pub win: f64,
+    /// Life points paid for the extra draws, on average over the won fights.
+    pub life_cost: f64,
+}
+
+/// Estimates the odds of the current fight, over `SAMPLES` deals of the
+/// cards the player can't see.
+///
+/// Only what a player knows is used: the cards left in the decks, not their
+/// order, and the aging cards that come in when the fighting deck is
+/// reshuffled. Returns `None` out of a fight, or while a capacity is used.
+pub fn fight_odds(game: &Friday) -> Option<FightOdds> {
+    match &game.state {
+        State::Fighting(_, _, _, _, Using::None) => {}
+        _ => return None,
+    }
+
+    let won = |game: &Friday| game.fight_diff().is_some_and(|diff| diff >= 0);
+    let mut win_free = 0;
+    let mut life_lost = 0;
+    let mut win = 0;
+    let mut life_cost = 0;
+    for seed in 0..SAMPLES {
+        let mut game = game.hypothetical(seed);
+        while !won(&game) && game.free_draws_left() > 0 {
+            if game.next(Event::Fight).is_err() {
+                break;
+            }
+        }
+        if won(&game) {
+            win_free += 1;
+            win += 1;
+            continue;
+        }
+        life_lost -= game.fight_diff().unwrap_or(0);
+
+        let life_points = game.life_points;
+        while !won(&game) && !game.is_ended() {
+            if game.next(Event::Fight).is_err() {
+                break;
+            }
+        }
+        if won(&game) {
+            win += 1;
+            life_cost += life_points - game.life_points;
+        }
+    }
+
+    let mean = |total: isize, n: u64| if n > 0 { total as f64 / n as f64 } else { 0.0 };
+    Some(FightOdds {
+        win_free: win_free as f64 / SAMPLES as f64,
+        life_lost: mean(life_lost, SAMPLES - win_free),
+        win: win as f64 / SAMPLES as f64,
+        life_cost: mean(life_cost, win),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::FridayBuilder;
+    use crate::cards::{CardDescription, CardId, CardSet};
+
+    fn starting(title: &str) -> CardId {
+        let f = |c: &CardDescription| c.is_starting() && c.kind.to_fighting_card().title == title;
+        CardSet::builtin().ids(f).next().unwrap()
+    }
+
+    #[test]
+    fn test_fight_odds() {
+        let set = CardSet::builtin();
+        let island = set
+            .ids(|c| {
+                c.is_hazard_knowledge()
+                    && c.kind.to_hazard_card().title == "Exploring the island"
+                    && c.kind.to_fighting_card().title == "weapon"
+            })
+            .next()
+            .unwrap();
+        let (weak, genius) = (starting("weak"), starting("genius"));
+        // genius on top, but the player can't know it
+        let game = FridayBuilder::new(1)
+            .life_points(10)
+            .fighting_deck(&[weak, weak, weak, genius])
+            .fight(island)
+            .build();
+        let odds = fight_odds(&game).unwrap();
+        // 2 free draws out of 4 cards, then 1 or 2 more paid half the time
+        assert!((odds.win_free - 0.5).abs() < 0.05, "{:?}", odds);
+        assert_eq!(odds.life_lost, 1.0);
+        assert_eq!(odds.win, 1.0);
+        assert!((odds.life_cost - 0.75).abs() < 0.1, "{:?}", odds);
+        assert_eq!(fight_odds(&game), Some(odds));
+
+        let game = FridayBuilder::new(1).build();
+        assert_eq!(fight_odds(&game), None);
+    }
+}
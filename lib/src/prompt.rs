@@ -0,0 +1,299 @@
+use std::fmt;
+
+use crate::cards::{Card, Handle};
+use crate::friday::{Friday, State, Using};
+
+/// What the player is asked for.
+///
+/// Not `Deserialize`: the cards to sort would have no description.
+#[derive(Debug, Clone, Serialize)]
+pub enum Ask {
+    /// One of the hazards drawn, with `Event::HazardChoice`.
+    Hazard { may_skip: bool },
+    /// The pirate to fight, with `Event::PirateChoice`.
+    Pirate,
+    /// Draw with `Event::Fight`, use a card with `Event::Use`, or end the
+    /// fight.
+    Fight { can_win: bool, can_lose: bool },
+    /// More cards to draw for free with `Event::Continue`.
+    Draw { left: i8 },
+    /// A card to destroy, other than `card`.
+    Destroy { card: Handle },
+    /// A card to double.
+    Double,
+    /// A card to copy.
+    Copy,
+    /// Cards to exchange, other than `card`.
+    Swap { card: Handle, left: i8 },
+    /// A card to put under the fighting deck, other than `card`. Only the
+    /// cards in `replace` may be replaced by a new one.
+    UnderDeck { card: Handle, replace: Vec<Handle> },
+    /// The order to put back the cards drawn, with `Event::Sort`.
+    Sort { cards: Vec<Card>, may_discard: bool },
+}
+
+/// The input a game waits for, for frontends to ask the player.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    pub ask: Ask,
+    /// Indices of the hazards or pirates the player may choose.
+    pub targets: Vec<usize>,
+    /// Cards of the fight the player may choose or use.
+    pub cards: Vec<Handle>,
+    pub can_continue: bool,
+    /// Whether `Event::Break` stops using a capacity.
+    pub can_break: bool,
+}
+
+impl fmt::Display for Prompt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = self.targets.len();
+        match &self.ask {
+            Ask::Hazard { may_skip } => {
+                write!(f, "choose one of {} hazards", n)?;
+                if *may_skip {
+                    write!(f, " (may skip)")?;
+                }
+                Ok(())
+            }
+            Ask::Pirate => write!(f, "choose one of {} pirates", n),
+            Ask::Fight { can_win, can_lose } => {
+                let mut choices = vec!["draw a card".to_string()];
+                if !self.cards.is_empty() {
+                    let cards: Vec<_> = self.cards.iter().map(|h| format!("#{}", h)).collect();
+                    choices.push(format!("use {}", cards.join(", ")));
+                }
+                if *can_win {
+                    choices.push("win".to_string());
+                }
+                if *can_lose {
+                    choices.push("lose".to_string());
+                }
+                write!(f, "fight: {}", choices.join(", "))
+            }
+            Ask::Draw { left } => write!(f, "draw: {} cards remaining", left),
+            Ask::Destroy { card } => write!(f, "choose a card to destroy, excluding #{}", card),
+            Ask::Double => write!(f, "choose a card to double"),
+            Ask::Copy => write!(f, "choose a card to copy"),
+            Ask::Swap { left, .. } => write!(f, "swap: {} exchanges remaining", left),
+            Ask::UnderDeck { card, .. } => write!(
+                f,
+                "choose a card to put under the deck, excluding #{}",
+                card
+            ),
+            Ask::Sort { cards, may_discard } => {
+                if cards.is_empty() {
+                    return write!(f, "draw up to 3 cards to sort");
+                }
+                write!(f, "sort these {} cards", cards.len())?;
+                if *may_discard {
+                    write!(f, ", optionally discarding one")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Friday {
+    /// What the game waits for, `None` once it's over.
+    ///
+    /// Worked out from the state, as `legal_events()` would be too costly:
+    /// it lists every discard the player may lose with.
+    pub fn prompt(&self) -> Option<Prompt> {
+        let (c, used_free, left, right, using) = match &self.state {
+            State::ChooseHazard(h) => {
+                let ask = Ask::Hazard {
+                    may_skip: h.len() == 1,
+                };
+                return Some(Prompt::choose(ask, h.len()));
+            }
+            State::ChoosePirate => {
+                return Some(Prompt::choose(Ask::Pirate, self.pirate_cards.len()))
+            }
+            State::Fighting(c, used_free, left, right, using) => {
+                (c, *used_free, left, right, using)
+            }
+            State::None | State::Ended(_) => return None,
+        };
+        let targets = || left.iter().chain(right.iter()).filter(|c| !c.destroy);
+
+        let cards = match using {
+            Using::None => handles(targets().filter(|c| {
+                !c.tapped
+                    && c.description()
+                        .kind
+                        .to_fighting_card()
+                        .capacity
+                        .is_active(&self.card_set)
+            })),
+            Using::Destroy(d) | Using::Swap(d, _) | Using::UnderDeck(d) => {
+                handles(targets().filter(|c| c.handle != *d))
+            }
+            Using::Double => handles(targets().filter(|c| !c.double)),
+            Using::Copy => handles(targets()),
+            Using::Draw(_) | Using::Sort(_) => vec![],
+        };
+        let ask = match using {
+            Using::None => Ask::Fight {
+                can_win: used_free > 0 && self.fight_diff().unwrap() >= 0,
+                can_lose: used_free > 0 && !c.description().is_pirate(),
+            },
+            Using::Draw(left) => Ask::Draw { left: *left },
+            Using::Destroy(card) => Ask::Destroy { card: *card },
+            Using::Double => Ask::Double,
+            Using::Copy => Ask::Copy,
+            Using::Swap(card, left) => Ask::Swap {
+                card: *card,
+                left: *left,
+            },
+            // only the free cards, on the left, may be replaced
+            Using::UnderDeck(card) => Ask::UnderDeck {
+                card: *card,
+                replace: handles(left.iter().filter(|c| !c.destroy && c.handle != *card)),
+            },
+            Using::Sort(cards) => Ask::Sort {
+                cards: cards.clone(),
+                may_discard: !cards.is_empty(),
+            },
+        };
+        let (can_continue, can_break) = match using {
+            Using::None => (false, false),
+            Using::Draw(_) => (true, true),
+            // the cards drawn to sort must be put back first
+            Using::Sort(v) => (v.len() < 3, v.is_empty()),
+            _ => (false, true),
+        };
+
+        Some(Prompt {
+            ask,
+            targets: vec![],
+            cards,
+            can_continue,
+            can_break,
+        })
+    }
+}
+
+fn handles<'a>(cards: impl Iterator<Item = &'a Card>) -> Vec<Handle> {
+    cards.map(|c| c.handle).collect()
+}
+
+impl Prompt {
+    /// Asks for one of `n` hazards or pirates.
+    fn choose(ask: Ask, n: usize) -> Self {
+        Prompt {
+            ask,
+            targets: (0..n).collect(),
+            cards: vec![],
+            can_continue: false,
+            can_break: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    use crate::builder::FridayBuilder;
+    use crate::cards::{Capacity, CardDescription, CardId, CardSet};
+    use crate::friday::Event;
+
+    fn find<F: Fn(&CardDescription) -> bool>(f: F) -> CardId {
+        CardSet::builtin().ids(f).next().unwrap()
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut game = Friday::with_seed(1, 7).unwrap();
+        let prompt = game.prompt().unwrap();
+        assert_eq!(prompt.targets, vec![0, 1]);
+        assert_eq!(prompt.to_string(), "choose one of 2 hazards");
+
+        let destroy =
+            find(|c| !c.is_pirate() && c.kind.to_fighting_card().capacity == Capacity::Destroy);
+        let swap =
+            find(|c| !c.is_pirate() && c.kind.to_fighting_card().capacity == Capacity::Swap(2));
+        let island = find(|c| {
+            c.is_hazard_knowledge() && c.kind.to_hazard_card().title == "Exploring the island"
+        });
+        game = FridayBuilder::new(1)
+            .fighting_deck(&[swap, swap, destroy])
+            .fight(island)
+            .build();
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Fight).unwrap();
+        let prompt = game.prompt().unwrap();
+        assert_eq!(prompt.cards, vec![Handle(1), Handle(2), Handle(3)]);
+        assert!(!prompt.can_break);
+
+        game.next(Event::Use(Handle(1))).unwrap();
+        let prompt = game.prompt().unwrap();
+        assert!(matches!(prompt.ask, Ask::Destroy { card: Handle(1) }));
+        assert_eq!(prompt.cards, vec![Handle(2), Handle(3)]);
+        assert_eq!(prompt.to_string(), "choose a card to destroy, excluding #1");
+        assert!(prompt.can_break);
+
+        game.next(Event::Break).unwrap();
+        game.next(Event::Use(Handle(2))).unwrap();
+        let prompt = game.prompt().unwrap();
+        assert_eq!(prompt.to_string(), "swap: 2 exchanges remaining");
+        assert!(!prompt.can_continue);
+    }
+
+    #[test]
+    fn test_prompt_legal_events() {
+        for seed in 0..20 {
+            let mut rng = XorShiftRng::from_seed([seed as u32 + 1, 2, 3, 4]);
+            let mut game = Friday::with_seed(1 + seed as usize % 4, seed).unwrap();
+
+            while let Some(prompt) = game.prompt() {
+                let events = game.legal_events();
+                let has = |e: Event| events.contains(&e);
+                let mut targets = vec![];
+                let mut cards = vec![];
+                let mut replace = vec![];
+                for event in events.iter() {
+                    match *event {
+                        Event::HazardChoice(Some(i)) | Event::PirateChoice(i) => targets.push(i),
+                        Event::Choice(h) | Event::Use(h) | Event::ChoiceUnder(h, false) => {
+                            cards.push(h)
+                        }
+                        Event::ChoiceUnder(h, true) => replace.push(h),
+                        _ => {}
+                    }
+                }
+                assert_eq!(prompt.targets, targets);
+                assert_eq!(prompt.cards, cards);
+                assert_eq!(prompt.can_continue, has(Event::Continue));
+                match &prompt.ask {
+                    Ask::Hazard { may_skip } => {
+                        assert_eq!(*may_skip, has(Event::HazardChoice(None)))
+                    }
+                    Ask::Fight { can_win, can_lose } => {
+                        assert_eq!(*can_win, has(Event::Win));
+                        assert_eq!(*can_lose, has(Event::Lose(vec![])));
+                        assert!(!prompt.can_break);
+                    }
+                    Ask::UnderDeck { replace: r, .. } => assert_eq!(*r, replace),
+                    Ask::Sort { may_discard, .. } => assert_eq!(
+                        *may_discard,
+                        events.iter().any(|e| matches!(e, Event::Sort(_, true)))
+                    ),
+                    _ => {}
+                }
+                if !matches!(prompt.ask, Ask::Fight { .. }) {
+                    assert_eq!(prompt.can_break, has(Event::Break));
+                }
+
+                let moves: Vec<_> = events.into_iter().filter(|e| *e != Event::Break).collect();
+                let event = rng.choose(&moves).unwrap().clone();
+                game.next(event).unwrap();
+            }
+        }
+    }
+}
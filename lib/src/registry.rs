@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Extensions registered on a card set, known by name in its cards.
+pub(crate) struct Registry<T: ?Sized> {
+    entries: HashMap<String, Box<T>>,
+}
+
+impl<T: ?Sized> Registry<T> {
+    /// Registers `value` as `name`, in place of the previous one.
+    pub fn set(&mut self, name: &str, value: Box<T>) {
+        self.entries.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.entries.get(name).map(|v| &**v)
+    }
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Registry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.entries.keys()).finish()
+    }
+}
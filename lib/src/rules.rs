@@ -0,0 +1,192 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+
+extern crate serde_json;
+
+use crate::cards::CardSet;
+use crate::friday::is_aging_at;
+
+/// Setup knobs of a game, from one of the four levels or from house rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    /// Difficulty level, 1 to 4, the rules are based on.
+    pub level: usize,
+    pub life_points: u8,
+    pub max_life_points: u8,
+    /// Number of pirates to fight at the end.
+    pub pirates: usize,
+    /// Aging cards shuffled into the fighting deck before the game starts.
+    pub extra_aging: usize,
+    /// Whether the "very stupid" aging cards are played.
+    pub very_stupid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleError {
+    /// Levels go from 1 to 4.
+    InvalidLevel(usize),
+    /// Starting life must be between 1 and the maximum.
+    InvalidLifePoints { life_points: u8, max: u8 },
+    /// There must be 1 pirate at least, and no more than in the card set.
+    InvalidPirates { pirates: usize, max: usize },
+    /// No more extra aging cards than in the aging deck.
+    TooManyAging { extra_aging: usize, max: usize },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::RuleError::*;
+
+        match self {
+            InvalidLevel(l) => write!(f, "Invalid level {}, expected 1 to 4", l),
+            InvalidLifePoints { life_points, max } => write!(
+                f,
+                "Invalid life points {}, expected 1 to {}",
+                life_points, max
+            ),
+            InvalidPirates { pirates, max } => {
+                write!(f, "Invalid pirates {}, expected 1 to {}", pirates, max)
+            }
+            TooManyAging { extra_aging, max } => write!(
+                f,
+                "Too many extra aging cards {}, expected at most {}",
+                extra_aging, max
+            ),
+        }
+    }
+}
+
+impl error::Error for RuleError {}
+
+/// A rules file: a level, and the knobs that differ from it.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    level: usize,
+    life_points: Option<u8>,
+    max_life_points: Option<u8>,
+    pirates: Option<usize>,
+    extra_aging: Option<usize>,
+    very_stupid: Option<bool>,
+}
+
+impl RuleSet {
+    /// The rules of the game at `level`.
+    pub fn level(level: usize) -> Result<Self, RuleError> {
+        if !(1..=4).contains(&level) {
+            return Err(RuleError::InvalidLevel(level));
+        }
+        Ok(Self {
+            level,
+            life_points: if level == 4 { 18 } else { 20 },
+            max_life_points: if level == 4 { 20 } else { 22 },
+            pirates: 2,
+            extra_aging: if level >= 2 { 1 } else { 0 },
+            very_stupid: level >= 3,
+        })
+    }
+
+    /// Reads house rules, as a `level` and any of the fields to change.
+    pub fn from_json<R: Read>(reader: R) -> io::Result<Self> {
+        let file: RuleFile = serde_json::from_reader(reader)?;
+        let invalid = |e: RuleError| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+        let mut rules = Self::level(file.level).map_err(invalid)?;
+        rules.life_points = file.life_points.unwrap_or(rules.life_points);
+        rules.max_life_points = file.max_life_points.unwrap_or(rules.max_life_points);
+        rules.pirates = file.pirates.unwrap_or(rules.pirates);
+        rules.extra_aging = file.extra_aging.unwrap_or(rules.extra_aging);
+        rules.very_stupid = file.very_stupid.unwrap_or(rules.very_stupid);
+        Ok(rules)
+    }
+
+    /// Writes the rules in the form read by `from_json()`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Checks that a game with `card_set` can be dealt with these rules.
+    pub fn validate(&self, card_set: &CardSet) -> Result<(), RuleError> {
+        if !(1..=4).contains(&self.level) {
+            return Err(RuleError::InvalidLevel(self.level));
+        }
+        if self.life_points < 1 || self.life_points > self.max_life_points {
+            return Err(RuleError::InvalidLifePoints {
+                life_points: self.life_points,
+                max: self.max_life_points,
+            });
+        }
+        let pirates = card_set.ids(|c| c.is_pirate()).count();
+        if self.pirates < 1 || self.pirates > pirates {
+            return Err(RuleError::InvalidPirates {
+                pirates: self.pirates,
+                max: pirates,
+            });
+        }
+        let aging = card_set
+            .ids(|c| is_aging_at(c, self))
+            .map(|id| card_set[id].start_qty)
+            .sum();
+        if self.extra_aging > aging {
+            return Err(RuleError::TooManyAging {
+                extra_aging: self.extra_aging,
+                max: aging,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let set = CardSet::builtin();
+        for level in 1..=4 {
+            assert_eq!(RuleSet::level(level).unwrap().validate(&set), Ok(()));
+        }
+        assert_eq!(RuleSet::level(0), Err(RuleError::InvalidLevel(0)));
+        assert_eq!(RuleSet::level(5), Err(RuleError::InvalidLevel(5)));
+
+        let rules = RuleSet::from_json(&br#"{"level": 4, "pirates": 1}"#[..]).unwrap();
+        assert_eq!(rules.life_points, 18);
+        assert_eq!(rules.pirates, 1);
+        assert_eq!(rules.validate(&set), Ok(()));
+        assert!(RuleSet::from_json(&br#"{"level": 7}"#[..]).is_err());
+        assert!(RuleSet::from_json(&br#"{"level": 1, "lives": 3}"#[..]).is_err());
+        assert_eq!(
+            RuleSet::from_json(rules.to_json().as_bytes()).unwrap(),
+            rules
+        );
+
+        let mut rules = RuleSet::level(1).unwrap();
+        rules.life_points = 23;
+        assert_eq!(
+            rules.validate(&set),
+            Err(RuleError::InvalidLifePoints {
+                life_points: 23,
+                max: 22
+            })
+        );
+        rules.life_points = 10;
+        rules.pirates = 11;
+        assert_eq!(
+            rules.validate(&set),
+            Err(RuleError::InvalidPirates {
+                pirates: 11,
+                max: 10
+            })
+        );
+        rules.pirates = 2;
+        rules.extra_aging = 11;
+        assert_eq!(
+            rules.validate(&set),
+            Err(RuleError::TooManyAging {
+                extra_aging: 11,
+                max: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::{self, Read, Write};
+
+extern crate serde_json;
+
+use crate::friday::Friday;
+
+/// Version of the save file format, to be bumped on incompatible changes.
+const VERSION: u32 = 4;
+
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    game: &'a Friday,
+}
+
+#[derive(Deserialize)]
+struct LoadFile {
+    version: u32,
+    game: serde_json::Value,
+}
+
+impl Friday {
+    /// Writes the full game state as JSON.
+    pub fn save<W: Write>(&self, writer: W) -> io::Result<()> {
+        let file = SaveFile {
+            version: VERSION,
+            game: self,
+        };
+        serde_json::to_writer_pretty(writer, &file)?;
+        Ok(())
+    }
+
+    /// Reads back a game written by `save()`.
+    pub fn load<R: Read>(reader: R) -> io::Result<Self> {
+        let file: LoadFile = serde_json::from_reader(reader)?;
+        if file.version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported save version {}", file.version),
+            ));
+        }
+        Ok(serde_json::from_value(file.game)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    use crate::friday::Event;
+
+    #[test]
+    fn test_save_load() {
+        let mut game = Friday::with_seed(4, 99).unwrap();
+        game.next(Event::HazardChoice(Some(1))).unwrap();
+        game.next(Event::Fight).unwrap();
+        game.next(Event::Fight).unwrap();
+
+        let mut buf = vec![];
+        game.save(&mut buf).unwrap();
+        let mut loaded = Friday::load(&buf[..]).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", game));
+
+        // the hidden deck order and the following shuffles are kept too
+        for _ in 0..20 {
+            let _ = game.next(Event::Fight);
+            let _ = loaded.next(Event::Fight);
+        }
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", game));
+    }
+
+    #[test]
+    fn test_load_descriptions() {
+        let mut game = Friday::with_seed(2, 5).unwrap();
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        game.next(Event::Fight).unwrap();
+
+        let mut buf = vec![];
+        game.save(&mut buf).unwrap();
+        // the descriptions are only in the card set
+        let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert!(json["game"]["fighting_deck"][0]
+            .get("description")
+            .is_none());
+
+        let loaded = Friday::load(&buf[..]).unwrap();
+        let left = loaded.get_left().unwrap();
+        for card in loaded.fighting_deck.iter().chain(left.iter()) {
+            assert!(ptr::eq(card.description(), &loaded.card_set[card.id]));
+        }
+
+        // so is a game read without load()
+        let read: Friday = serde_json::from_value(json["game"].clone()).unwrap();
+        assert_eq!(read.legal_events(), game.legal_events());
+    }
+
+    #[test]
+    fn test_load_version() {
+        let err = Friday::load(&br#"{"version": 0, "game": {}}"#[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,311 @@
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use crate::cards::{Capacity, Card, FightingCard, Handle, Step};
+use crate::discard::{plan_discard, DiscardPriorities};
+use crate::error::GameError;
+use crate::friday::{Event, Friday, State, Using};
+use crate::view::{Objective, PlayerView};
+
+/// A player, picking the next event to play from what it sees of the game.
+pub trait Strategy {
+    fn choose(&mut self, view: &PlayerView) -> Event;
+}
+
+/// Plays `game` with `strategy` until the game ends.
+///
+/// Fails if the strategy picks an event that is rejected.
+pub fn play<S: Strategy + ?Sized>(game: &mut Friday, strategy: &mut S) -> Result<(), GameError> {
+    while !game.is_ended() {
+        let event = strategy.choose(&game.player_view());
+        game.next(event)?;
+    }
+    Ok(())
+}
+
+/// Plays any of the legal events, except `Break`, and loses with a random
+/// discard.
+#[derive(Debug)]
+pub struct RandomStrategy {
+    rng: XorShiftRng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        // XorShift needs a non-zero seed
+        let seed = [seed as u32, (seed >> 32) as u32, 0x9e37_79b9, 1];
+        Self {
+            rng: XorShiftRng::from_seed(seed),
+        }
+    }
+
+    /// Cards of the fight picked at random, while their destroy value fits.
+    fn random_discard(&mut self, view: &PlayerView) -> Vec<Handle> {
+        let mut budget = view.discard_budget().unwrap_or(0);
+        let mut cards: Vec<&Card> = match &view.state {
+            State::Fighting(_, _, left, right, _) => left
+                .iter()
+                .chain(right.iter())
+                .filter(|c| !c.destroy)
+                .collect(),
+            _ => vec![],
+        };
+        self.rng.shuffle(&mut cards);
+        let mut discard = vec![];
+        for c in cards {
+            let cost = c.description().get_destroy_value().unwrap();
+            if cost <= budget {
+                budget -= cost;
+                discard.push(c.handle);
+            }
+        }
+        discard
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn choose(&mut self, view: &PlayerView) -> Event {
+        let events: Vec<_> = view
+            .legal_events()
+            .into_iter()
+            .filter(|e| *e != Event::Break)
+            .collect();
+        match self.rng.choose(&events).cloned() {
+            Some(Event::Lose(_)) => Event::Lose(self.random_discard(view)),
+            event => event.unwrap_or(Event::Break),
+        }
+    }
+}
+
+/// Fights the easiest hazard, draws until it can win and never uses a capacity.
+#[derive(Debug, Default)]
+pub struct GreedyStrategy;
+
+impl Strategy for GreedyStrategy {
+    fn choose(&mut self, view: &PlayerView) -> Event {
+        match &view.state {
+            State::ChooseHazard(_) => {
+                let objectives = &view.objectives;
+                let easiest = (0..objectives.len())
+                    .min_by_key(|i| objectives[*i].level)
+                    .unwrap();
+                Event::HazardChoice(Some(easiest))
+            }
+            State::ChoosePirate => Event::PirateChoice(0),
+            State::Fighting(_, _, _, _, Using::None) => fight_or_give_up(view, vec![]),
+            _ => Event::Break,
+        }
+    }
+}
+
+/// Weighs hazard choices, uses capacities when the fight is not won yet, and
+/// gets rid of aging and weak cards when losing.
+#[derive(Debug, Default)]
+pub struct HeuristicStrategy;
+
+impl Strategy for HeuristicStrategy {
+    fn choose(&mut self, view: &PlayerView) -> Event {
+        let objectives = &view.objectives;
+        let (left, right, using) = match &view.state {
+            State::ChooseHazard(h) => {
+                let average = deck_average(view);
+                let best = (0..h.len())
+                    .max_by_key(|i| hazard_score(&h[*i], &objectives[*i], average))
+                    .unwrap();
+                return Event::HazardChoice(Some(best));
+            }
+            State::ChoosePirate => {
+                let average = deck_average(view);
+                let easiest = (0..objectives.len())
+                    .min_by_key(|i| pirate_difficulty(&objectives[*i], average))
+                    .unwrap();
+                return Event::PirateChoice(easiest);
+            }
+            State::Fighting(_, _, left, right, using) => (left, right, using),
+            _ => return Event::Break,
+        };
+        let cards: Vec<&Card> = left.iter().chain(right.iter()).collect();
+        // the worst card, other than the one being used
+        let worst = |except: Handle| {
+            (0..cards.len())
+                .filter(|i| {
+                    cards[*i].handle != except && !cards[*i].destroy && card_score(cards[*i]) <= 0
+                })
+                .min_by_key(|i| card_score(cards[*i]))
+        };
+        let choice = |i: usize| Event::Choice(cards[i].handle);
+
+        match *using {
+            Using::None => match capacity_to_use(view, &cards) {
+                Some(i) => Event::Use(cards[i].handle),
+                None => {
+                    let discard = plan_discard(view, &DiscardPriorities::default());
+                    fight_or_give_up(view, discard.unwrap_or_default())
+                }
+            },
+            Using::Draw(_) => Event::Continue,
+            Using::Destroy(d) => worst(d).map_or(Event::Break, choice),
+            Using::Swap(d, _) => worst(d).map_or(Event::Break, choice),
+            // a used card put below an empty stack would come back untapped, forever
+            Using::UnderDeck(d) => worst(d)
+                .filter(|i| !cards[*i].tapped)
+                .map_or(Event::Break, |i| {
+                    Event::ChoiceUnder(cards[i].handle, i < left.len())
+                }),
+            Using::Double => (0..cards.len())
+                .filter(|i| !cards[*i].destroy && !cards[*i].double)
+                .filter(|i| cards[*i].get_fighting_value() > 0)
+                .max_by_key(|i| cards[*i].get_fighting_value())
+                .map_or(Event::Break, choice),
+            Using::Copy => (0..cards.len())
+                .filter(|i| !cards[*i].destroy)
+                .filter(|i| {
+                    let capacity = &fighting_card(cards[*i]).capacity;
+                    match capacity {
+                        Capacity::Copy => false,
+                        _ => capacity_is_useful(view, &cards, *i, capacity),
+                    }
+                })
+                .max_by_key(|i| capacity_value(&fighting_card(cards[*i]).capacity))
+                .map_or(Event::Break, choice),
+            Using::Sort(ref v) => {
+                if v.len() < 3 && view.fighting_deck > 0 {
+                    return Event::Continue;
+                }
+                // the last sorted card goes on top of the stack
+                let mut order: Vec<_> = (0..v.len()).collect();
+                order.sort_by_key(|i| card_score(&v[*i]));
+                let destroy = order.first().is_some_and(|i| card_score(&v[*i]) < 0);
+                Event::Sort(order, destroy)
+            }
+        }
+    }
+}
+
+fn fighting_card(card: &Card) -> &FightingCard {
+    card.description().kind.to_fighting_card()
+}
+
+/// How much a card helps in a fight, aging penalties included.
+pub(crate) fn card_score(card: &Card) -> isize {
+    let penalty = match fighting_card(card).capacity {
+        Capacity::MaxZero | Capacity::Stop => -3,
+        Capacity::EndLife(n) => n as isize,
+        _ => 0,
+    };
+    card.get_fighting_value() + penalty
+}
+
+/// Rough worth of a capacity, in fighting points.
+pub(crate) fn capacity_value(capacity: &Capacity) -> isize {
+    match capacity {
+        Capacity::Life(n) | Capacity::Card(n) | Capacity::Swap(n) => *n as isize,
+        Capacity::Destroy => 2,
+        Capacity::Double
+        | Capacity::Copy
+        | Capacity::LowerStep
+        | Capacity::Sort
+        | Capacity::UnderDeck => 1,
+        _ => 0,
+    }
+}
+
+/// Average fighting value of the cards owned by the player.
+fn deck_average(view: &PlayerView) -> isize {
+    let values: Vec<_> = view
+        .fighting_deck_cards
+        .iter()
+        .map(|id| view.card_set.card(*id).get_fighting_value())
+        .chain(view.fighting_discard.iter().map(|c| c.get_fighting_value()))
+        .collect();
+    if values.is_empty() {
+        return 0;
+    }
+    values.iter().sum::<isize>() / values.len() as isize
+}
+
+fn hazard_score(card: &Card, objective: &Objective, average: isize) -> isize {
+    let knowledge = card.description().kind.to_fighting_card();
+    let expected = objective.free_cards as isize * average - objective.level as isize;
+    knowledge.fighting_value + capacity_value(&knowledge.capacity) + expected.min(0)
+}
+
+fn pirate_difficulty(objective: &Objective, average: isize) -> isize {
+    objective.level as isize - objective.free_cards as isize * average
+}
+
+/// Whether using `capacity` now, from card `i`, would help.
+fn capacity_is_useful(view: &PlayerView, cards: &[&Card], i: usize, capacity: &Capacity) -> bool {
+    let losing = view.fight_diff.unwrap_or(0) < 0;
+    let others = || {
+        cards
+            .iter()
+            .enumerate()
+            .filter(move |(j, c)| *j != i && !c.destroy)
+            .map(|(_, c)| c)
+    };
+    match capacity {
+        Capacity::Life(n) => view.life_points + *n as isize <= view.max_life_points as isize,
+        Capacity::Card(_) | Capacity::Sort => losing,
+        Capacity::Destroy | Capacity::Swap(_) | Capacity::UnderDeck => {
+            losing && others().any(|c| card_score(c) <= 0)
+        }
+        Capacity::Double => losing && others().any(|c| !c.double && c.get_fighting_value() > 0),
+        Capacity::LowerStep => {
+            losing && view.step != Step::Pirate && (view.step as usize) > view.step_modif
+        }
+        Capacity::Copy => losing && others().any(|c| fighting_card(c).capacity != Capacity::Copy),
+        _ => false,
+    }
+}
+
+fn capacity_to_use(view: &PlayerView, cards: &[&Card]) -> Option<usize> {
+    (0..cards.len()).find(|i| {
+        let c = cards[*i];
+        let capacity = &fighting_card(c).capacity;
+        !c.tapped && !c.destroy && capacity_is_useful(view, cards, *i, capacity)
+    })
+}
+
+/// Wins if possible, otherwise draws the free cards and then gives up.
+///
+/// Paid draws are only worth it against pirates, since they can't be lost.
+fn fight_or_give_up(view: &PlayerView, discard: Vec<Handle>) -> Event {
+    let (pirate, used_free) = match &view.state {
+        State::Fighting(c, used_free, _, _, _) => (c.description().is_pirate(), *used_free),
+        _ => return Event::Break,
+    };
+
+    if used_free > 0 && view.fight_diff.unwrap() >= 0 {
+        Event::Win
+    } else if view.free_draws_left > 0 || used_free == 0 || pirate {
+        Event::Fight
+    } else {
+        Event::Lose(discard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn average_score<S: Strategy>(mut strategy: S, level: usize) -> isize {
+        let mut total = 0;
+        for seed in 0..30 {
+            let mut game = Friday::with_seed(level, seed).unwrap();
+            play(&mut game, &mut strategy).unwrap();
+            total += game.score();
+        }
+        total / 30
+    }
+
+    #[test]
+    fn test_strategies() {
+        for level in 1..=4 {
+            let random = average_score(RandomStrategy::new(level as u64), level);
+            let greedy = average_score(GreedyStrategy, level);
+            let heuristic = average_score(HeuristicStrategy, level);
+            assert!(random < heuristic, "level {}", level);
+            assert!(greedy < heuristic, "level {}", level);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
+
+extern crate serde_json;
+
+use crate::cards::{Capacity, CardKind, CardSet, Hazard};
+use crate::friday::is_aging_at;
+use crate::rules::RuleSet;
+
+/// Number of starting cards in the fighting deck.
+const STARTING_CARDS: usize = 18;
+/// Number of aging difficult cards, put at the bottom of the aging deck.
+const AGING_DIFFICULT_CARDS: usize = 2;
+/// Number of aging cards dealt at each level.
+const AGING_CARDS: [usize; 4] = [10, 10, 11, 11];
+
+/// A problem found in a card set file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Line of the card at fault, or of the syntax error.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Lines where each card of the `cards` list starts.
+fn card_lines(text: &str) -> Vec<usize> {
+    let mut lines = vec![];
+    let mut line = 1;
+    let mut nesting = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                if c == '{' && nesting[..] == ['{', '['] {
+                    lines.push(line);
+                }
+                nesting.push(c);
+            }
+            '}' | ']' => {
+                nesting.pop();
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Checks that the card set in `text`, as read by `CardSet::from_json()`,
+/// can be played.
+///
+/// Custom capacities and hazards are not checked: their effects and rules are
+/// registered on the set once loaded.
+pub fn validate(text: &str) -> Vec<Diagnostic> {
+    let set: CardSet = match serde_json::from_str(text) {
+        Ok(set) => set,
+        Err(err) => {
+            return vec![Diagnostic {
+                line: Some(err.line()),
+                message: err.to_string(),
+            }];
+        }
+    };
+    let lines = card_lines(text);
+    let mut diags = vec![];
+    let mut error = |i: Option<usize>, message: String| {
+        diags.push(Diagnostic {
+            line: i.and_then(|i| lines.get(i).cloned()),
+            message,
+        });
+    };
+
+    if set.len() > u8::MAX as usize + 1 {
+        error(None, format!("too many cards: {}", set.len()));
+        return diags;
+    }
+
+    let count = |f: &dyn Fn(&CardKind) -> bool| -> usize {
+        set.ids(|c| f(&c.kind)).map(|id| set[id].start_qty).sum()
+    };
+    let starting = count(&|k| matches!(k, CardKind::Starting(_)));
+    if starting != STARTING_CARDS {
+        error(
+            None,
+            format!("{} starting cards, expected {}", starting, STARTING_CARDS),
+        );
+    }
+    let difficult = count(&|k| matches!(k, CardKind::AgingDifficult(_)));
+    if difficult != AGING_DIFFICULT_CARDS {
+        error(
+            None,
+            format!(
+                "{} aging difficult cards, expected {}",
+                difficult, AGING_DIFFICULT_CARDS
+            ),
+        );
+    }
+    for (level, expected) in (1..=4).zip(AGING_CARDS.iter()) {
+        let aging: usize = set
+            .ids(|c| is_aging_at(c, &RuleSet::level(level).unwrap()))
+            .map(|id| set[id].start_qty)
+            .sum();
+        if aging != *expected {
+            error(
+                None,
+                format!(
+                    "level {} deals {} aging cards, expected {}",
+                    level, aging, expected
+                ),
+            );
+        }
+    }
+    let pirates = set.ids(|c| c.is_pirate()).count();
+    if pirates < 2 {
+        error(None, format!("{} pirates, at least 2 are needed", pirates));
+    }
+
+    let mut filenames = HashMap::new();
+    for (i, id) in set.ids(|_| true).enumerate() {
+        let card = &set[id];
+        let (hazard, fighting) = match &card.kind {
+            CardKind::Starting(f) | CardKind::AgingNormal(f) | CardKind::AgingDifficult(f) => {
+                (None, Some(f))
+            }
+            CardKind::HazardKnowledge(h, f) => (Some(h), Some(f)),
+            CardKind::Pirate(h) => (Some(h), None),
+        };
+
+        if let Some(f) = fighting {
+            match &f.capacity {
+                Capacity::Custom(_) => {}
+                c if c.text().is_none() => error(
+                    Some(i),
+                    format!("\"{}\": unknown capacity {:?}", f.title, c),
+                ),
+                _ => {}
+            }
+        }
+        if let Some(h) = hazard {
+            if h.hazard.is_special() && h.title.is_empty() {
+                error(
+                    Some(i),
+                    format!("pirate {:?} needs a title to tell its rule", h.hazard),
+                );
+            }
+            if let Hazard::Leveled(l) = h.hazard {
+                if l[0] > l[1] || l[1] > l[2] {
+                    error(
+                        Some(i),
+                        format!("\"{}\": hazard levels {} are not rising", h.title, h.hazard),
+                    );
+                }
+            }
+        }
+
+        if let Some(first) = filenames.insert(card.filename.clone(), i) {
+            let line = lines
+                .get(first)
+                .map_or(String::new(), |l| format!(" (line {})", l));
+            error(
+                Some(i),
+                format!(
+                    "image {} already used by card #{}{}",
+                    card.filename, first, line
+                ),
+            );
+        }
+    }
+
+    diags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILTIN: &str = include_str!("../data/cards.json");
+
+    #[test]
+    fn test_builtin() {
+        assert_eq!(validate(BUILTIN), vec![]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let text = BUILTIN.replacen("\"Life\": 2", "\"Life\": 3", 1).replacen(
+            "friday-033.jpg",
+            "friday-030.jpg",
+            1,
+        );
+        let diags: Vec<_> = validate(&text).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            diags,
+            vec![
+                "line 3: \"eating\": unknown capacity Life(3)",
+                "line 16: image friday-030.jpg already used by card #0 (line 3)",
+            ]
+        );
+
+        let text = r#"{"cards": [
+            {"kind": {"Pirate": {"title": "", "free_cards": 5, "hazard": "PirateAging"}},
+             "start_qty": 1, "filename": "p.jpg"}
+        ]}"#;
+        let diags = validate(text);
+        assert!(diags.contains(&Diagnostic {
+            line: Some(2),
+            message: "pirate PirateAging needs a title to tell its rule".to_string(),
+        }));
+        assert!(diags.contains(&Diagnostic {
+            line: None,
+            message: "1 pirates, at least 2 are needed".to_string(),
+        }));
+
+        let diags = validate("{\"cards\": [\n{]}");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].line, Some(2));
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::cards::{Card, CardId, CardSet, Step};
+use crate::friday::{Friday, State};
+use crate::rules::RuleSet;
+
+/// A hazard or a pirate the player may choose to fight.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Objective {
+    /// Fighting points to reach, at the current step.
+    pub level: u8,
+    pub free_cards: u8,
+}
+
+/// What a player at the table knows of a game.
+///
+/// Decks are only counted, except for the cards whose place the player
+/// knows, because they were sorted or put under the fighting deck.
+///
+/// Deserializing a view gives its cards their description from its card set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct PlayerView {
+    pub rules: RuleSet,
+    pub life_points: isize,
+    pub max_life_points: u8,
+    pub step: Step,
+    pub step_modif: usize,
+    pub aging_deck: usize,
+    pub fighting_deck: usize,
+    pub hazard_deck: usize,
+    /// The cards left in the fighting deck, by id: which ones is known, not
+    /// their order.
+    pub fighting_deck_cards: Vec<CardId>,
+    /// Cards sorted on top of the fighting deck, the last one drawn first.
+    pub fighting_top: Vec<Card>,
+    /// Cards put under the fighting deck, the first one drawn last.
+    pub fighting_bottom: Vec<Card>,
+    pub fighting_discard: Vec<Card>,
+    pub hazard_discard: Vec<Card>,
+    pub destroyed: Vec<Card>,
+    pub pirate_cards: Vec<Card>,
+    pub pirate_cards_won: Vec<Card>,
+    pub state: State,
+    /// The cards of the game, to look up the ids above.
+    pub card_set: Arc<CardSet>,
+    /// As `Friday::fight_diff()`.
+    pub fight_diff: Option<isize>,
+    /// As `Friday::free_draws_left()`.
+    pub free_draws_left: u8,
+    /// The hazards or the pirates to choose from, in order.
+    pub objectives: Vec<Objective>,
+}
+
+impl Serialize for PlayerView {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PlayerView::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PlayerView {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut view = PlayerView::deserialize(deserializer)?;
+        let set = &view.card_set;
+        for cards in [
+            &mut view.fighting_top,
+            &mut view.fighting_bottom,
+            &mut view.fighting_discard,
+            &mut view.hazard_discard,
+            &mut view.destroyed,
+            &mut view.pirate_cards,
+            &mut view.pirate_cards_won,
+        ]
+        .iter_mut()
+        {
+            set.attach(cards)
+                .map_err(|id| D::Error::custom(format!("unknown card {}", id.0)))?;
+        }
+        view.state
+            .attach(set)
+            .map_err(|id| D::Error::custom(format!("unknown card {}", id.0)))?;
+        Ok(view)
+    }
+}
+
+impl Friday {
+    /// The game as seen by the player, without the hidden order of the decks.
+    pub fn player_view(&self) -> PlayerView {
+        let (top, bottom) = self.known_fighting_cards();
+        let mut fighting_deck_cards: Vec<_> = self.fighting_deck.iter().map(|c| c.id).collect();
+        fighting_deck_cards.sort();
+        let objective = |c: &Card| {
+            let hazard = c.description().kind.to_hazard_card();
+            Objective {
+                level: self.hazard_level(&hazard.hazard, self.step),
+                free_cards: self.free_cards(hazard),
+            }
+        };
+        let objectives = match &self.state {
+            State::ChooseHazard(h) => h.iter().map(objective).collect(),
+            State::ChoosePirate => self.pirate_cards.iter().map(objective).collect(),
+            _ => vec![],
+        };
+
+        PlayerView {
+            rules: self.rules.clone(),
+            life_points: self.life_points,
+            max_life_points: self.max_life_points(),
+            step: self.step,
+            step_modif: self.step_modif,
+            aging_deck: self.aging_deck.len(),
+            fighting_deck: self.fighting_deck.len(),
+            hazard_deck: self.hazard_deck.len(),
+            fighting_deck_cards,
+            fighting_top: top.to_vec(),
+            fighting_bottom: bottom.to_vec(),
+            fighting_discard: self.fighting_discard.clone(),
+            hazard_discard: self.hazard_discard.clone(),
+            destroyed: self.destroyed.clone(),
+            pirate_cards: self.pirate_cards.clone(),
+            pirate_cards_won: self.pirate_cards_won.clone(),
+            state: self.state.clone(),
+            card_set: self.card_set.clone(),
+            fight_diff: self.fight_diff(),
+            free_draws_left: self.free_draws_left(),
+            objectives,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::friday::Event;
+
+    #[test]
+    fn test_player_view() {
+        let mut game = Friday::with_seed(2, 5).unwrap();
+        let view = game.player_view();
+        assert_eq!(view.fighting_deck, 19);
+        assert_eq!(view.aging_deck, 9);
+        assert!(view.fighting_top.is_empty());
+        assert!(view.fighting_bottom.is_empty());
+        assert_eq!(view.objectives.len(), 2);
+        assert_eq!(view.fight_diff, None);
+
+        // the same cards, dealt in another order, give the same view
+        let mut ids: Vec<_> = game.fighting_deck.iter().map(|c| c.id).collect();
+        ids.sort();
+        assert_eq!(view.fighting_deck_cards, ids);
+        game.fighting_deck.reverse();
+        let json = |v: &PlayerView| serde_json::to_string(v).unwrap();
+        assert_eq!(json(&game.player_view()), json(&view));
+
+        game.next(Event::HazardChoice(Some(0))).unwrap();
+        game.next(Event::Fight).unwrap();
+        let view = game.player_view();
+        assert_eq!(view.fighting_deck, 18);
+        assert_eq!(view.fight_diff, game.fight_diff());
+        assert!(view.objectives.is_empty());
+
+        // a view read back has its cards described
+        let back: PlayerView = serde_json::from_str(&json(&view)).unwrap();
+        assert_eq!(json(&back), json(&view));
+        assert_eq!(back.legal_events(), view.legal_events());
+        assert_eq!(back.discard_budget(), view.discard_budget());
+    }
+}
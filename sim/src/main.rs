@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::process;
+use std::sync::Arc;
+
+#[macro_use]
+extern crate clap;
+use clap::Arg;
+
+#[macro_use]
+extern crate serde_derive;
+
+extern crate friday_lib;
+use friday_lib::cards::{Card, CardSet};
+use friday_lib::error::GameError;
+use friday_lib::friday::{Effect, Event, Friday, State};
+use friday_lib::rules::RuleSet;
+use friday_lib::strategy::{GreedyStrategy, HeuristicStrategy, RandomStrategy, Strategy};
+
+/// What happened in one simulated game.
+#[derive(Debug, Serialize)]
+struct GameRecord {
+    level: usize,
+    seed: u64,
+    won: bool,
+    score: isize,
+    life: isize,
+    step: String,
+    pirates_won: usize,
+    aging_drawn: usize,
+    /// Life points when each pirate fight started.
+    pirate_life: Vec<isize>,
+    /// The fight that ended a lost game.
+    death: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScoreDistribution {
+    min: isize,
+    max: isize,
+    mean: f64,
+    median: isize,
+    /// Number of games per score bucket of 10 points, keyed by lower bound.
+    histogram: BTreeMap<isize, usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct LevelSummary {
+    level: usize,
+    games: usize,
+    wins: usize,
+    win_rate: f64,
+    score: ScoreDistribution,
+    /// Average life at the start of the 1st and 2nd pirate fights, when reached.
+    pirate_life: Vec<Option<f64>>,
+    aging_drawn_per_game: f64,
+    deaths: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    strategy: String,
+    seed: u64,
+    summary: Vec<LevelSummary>,
+    games: Vec<GameRecord>,
+}
+
+fn fighting(game: &Friday) -> Option<&Card> {
+    match &game.state {
+        State::Fighting(c, _, _, _, _) => Some(c),
+        _ => None,
+    }
+}
+
+fn fight_name(game: &Friday, card: &Card) -> String {
+    let hazard = card.description().kind.to_hazard_card();
+    if card.description().is_pirate() {
+        format!("pirate {:?}", hazard.hazard)
+    } else {
+        format!("{} ({:?})", hazard.title, game.step)
+    }
+}
+
+fn play_game(
+    rules: &RuleSet,
+    seed: u64,
+    cards: &Arc<CardSet>,
+    strategy: &mut dyn Strategy,
+) -> Result<GameRecord, GameError> {
+    let level = rules.level;
+    let mut game = Friday::with_rules(rules.clone(), seed, cards.clone())?;
+    let mut aging_drawn = 0;
+    let mut pirate_life = vec![];
+    let mut death = None;
+
+    while !game.is_ended() {
+        let before = fighting(&game).map(|c| fight_name(&game, c));
+        let event = strategy.choose(&game.player_view());
+        let is_fight_over = matches!(event, Event::Win | Event::Lose(_));
+        let effects = match game.next(event) {
+            Ok(effects) => effects,
+            Err(e) => {
+                eprintln!("seed {}: strategy played a rejected event: {}", seed, e);
+                break;
+            }
+        };
+        for effect in effects.iter() {
+            if let Effect::CardDrawn(id) = effect {
+                let card = &game.card_set[*id];
+                if card.is_aging_normal() || card.is_aging_difficult() {
+                    aging_drawn += 1;
+                }
+            }
+        }
+
+        match (&before, fighting(&game)) {
+            (Some(name), Some(c)) if !is_fight_over && *name == fight_name(&game, c) => {}
+            (_, Some(c)) => {
+                if c.description().is_pirate() {
+                    pirate_life.push(game.life_points);
+                }
+            }
+            (Some(name), None) => {
+                if let State::Ended(false) = game.state {
+                    death = Some(name.clone());
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(GameRecord {
+        level,
+        seed,
+        won: match game.state {
+            State::Ended(won) => won,
+            _ => false,
+        },
+        score: game.score(),
+        life: game.life_points,
+        step: format!("{:?}", game.step),
+        pirates_won: game.pirate_cards_won.len(),
+        aging_drawn,
+        pirate_life,
+        death,
+    })
+}
+
+fn summarize(level: usize, games: &[&GameRecord]) -> LevelSummary {
+    let n = games.len().max(1) as f64;
+    let mut scores: Vec<_> = games.iter().map(|g| g.score).collect();
+    scores.sort();
+    let mut histogram = BTreeMap::new();
+    for s in scores.iter() {
+        *histogram.entry(s.div_euclid(10) * 10).or_insert(0) += 1;
+    }
+    let pirate_life = (0..2)
+        .map(|i| {
+            let lives: Vec<_> = games.iter().filter_map(|g| g.pirate_life.get(i)).collect();
+            if lives.is_empty() {
+                return None;
+            }
+            Some(lives.iter().map(|l| **l as f64).sum::<f64>() / lives.len() as f64)
+        })
+        .collect();
+    let mut deaths = BTreeMap::new();
+    for d in games.iter().filter_map(|g| g.death.as_ref()) {
+        *deaths.entry(d.clone()).or_insert(0) += 1;
+    }
+    let wins = games.iter().filter(|g| g.won).count();
+
+    LevelSummary {
+        level,
+        games: games.len(),
+        wins,
+        win_rate: wins as f64 / n,
+        score: ScoreDistribution {
+            min: scores.first().cloned().unwrap_or(0),
+            max: scores.last().cloned().unwrap_or(0),
+            mean: scores.iter().sum::<isize>() as f64 / n,
+            median: scores.get(scores.len() / 2).cloned().unwrap_or(0),
+            histogram,
+        },
+        pirate_life,
+        aging_drawn_per_game: games.iter().map(|g| g.aging_drawn).sum::<usize>() as f64 / n,
+        deaths,
+    }
+}
+
+fn write_csv<W: Write>(mut w: W, games: &[GameRecord]) -> io::Result<()> {
+    writeln!(
+        w,
+        "level,seed,won,score,life,step,pirates_won,aging_drawn,pirate_life,death"
+    )?;
+    for g in games {
+        let pirate_life: Vec<_> = g.pirate_life.iter().map(|l| l.to_string()).collect();
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{},\"{}\"",
+            g.level,
+            g.seed,
+            g.won,
+            g.score,
+            g.life,
+            g.step,
+            g.pirates_won,
+            g.aging_drawn,
+            pirate_life.join(";"),
+            g.death.as_ref().map_or("", |d| d.as_str())
+        )?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let matches = clap_app!(("friday-sim") =>
+       (version: crate_version!())
+       (author: crate_authors!())
+       (about: "Plays Friday games with a built-in strategy")
+    )
+    .arg(
+        Arg::with_name("games")
+            .short("n")
+            .long("games")
+            .takes_value(true)
+            .value_name("N")
+            .default_value("100"),
+    )
+    .arg(
+        Arg::with_name("level")
+            .short("l")
+            .long("level")
+            .takes_value(true)
+            .multiple(true)
+            .value_name("LEVEL")
+            .possible_values(&["1", "2", "3", "4"]),
+    )
+    .arg(
+        Arg::with_name("seed")
+            .short("s")
+            .long("seed")
+            .takes_value(true)
+            .value_name("SEED")
+            .default_value("0"),
+    )
+    .arg(
+        Arg::with_name("strategy")
+            .long("strategy")
+            .takes_value(true)
+            .possible_values(&["random", "greedy", "heuristic"])
+            .default_value("heuristic"),
+    )
+    .arg(
+        Arg::with_name("format")
+            .short("f")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["json", "csv"])
+            .default_value("json"),
+    )
+    .arg(
+        Arg::with_name("cards")
+            .long("cards")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Plays with the card set of a JSON file"),
+    )
+    .arg(
+        Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .takes_value(true)
+            .value_name("FILE"),
+    )
+    .get_matches();
+
+    let n = value_t!(matches, "games", u64).unwrap_or_else(|e| e.exit());
+    let base_seed = value_t!(matches, "seed", u64).unwrap_or_else(|e| e.exit());
+    let levels = values_t!(matches, "level", usize).unwrap_or_else(|_| vec![1, 2, 3, 4]);
+    let strategy_name = matches.value_of("strategy").unwrap();
+    let cards = match matches.value_of("cards") {
+        Some(path) => match File::open(path).and_then(CardSet::from_json) {
+            Ok(cards) => Arc::new(cards),
+            Err(err) => {
+                eprintln!("Failed to load {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => CardSet::builtin(),
+    };
+
+    let mut games = vec![];
+    for level in levels.iter() {
+        let rules = match RuleSet::level(*level).and_then(|r| r.validate(&cards).map(|_| r)) {
+            Ok(rules) => rules,
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        };
+        let mut strategy: Box<dyn Strategy> = match strategy_name {
+            "random" => Box::new(RandomStrategy::new(base_seed)),
+            "greedy" => Box::new(GreedyStrategy),
+            _ => Box::new(HeuristicStrategy),
+        };
+        for i in 0..n {
+            match play_game(&rules, base_seed.wrapping_add(i), &cards, strategy.as_mut()) {
+                Ok(game) => games.push(game),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    let summary: Vec<_> = levels
+        .iter()
+        .map(|level| {
+            let level_games: Vec<_> = games.iter().filter(|g| g.level == *level).collect();
+            summarize(*level, &level_games)
+        })
+        .collect();
+    for s in summary.iter() {
+        eprintln!(
+            "level {}: {}/{} won ({:.1}%), mean score {:.1}, aging drawn {:.1}/game",
+            s.level,
+            s.wins,
+            s.games,
+            s.win_rate * 100.0,
+            s.score.mean,
+            s.aging_drawn_per_game
+        );
+    }
+
+    let output: Box<dyn Write> = match matches.value_of("output") {
+        Some(path) => match File::create(path) {
+            Ok(f) => Box::new(f),
+            Err(err) => {
+                eprintln!("Failed to create {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+    let res = match matches.value_of("format") {
+        Some("csv") => write_csv(output, &games),
+        _ => {
+            let report = Report {
+                strategy: strategy_name.to_string(),
+                seed: base_seed,
+                summary,
+                games,
+            };
+            serde_json::to_writer_pretty(output, &report).map_err(io::Error::from)
+        }
+    };
+    if let Err(err) = res {
+        eprintln!("Failed to write the results: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_game() {
+        let rules = RuleSet::level(1).unwrap();
+        let cards = CardSet::builtin();
+        let games: Vec<_> = (0..2)
+            .map(|seed| play_game(&rules, seed, &cards, &mut HeuristicStrategy).unwrap())
+            .collect();
+
+        assert!(!games[0].won);
+        assert_eq!(games[0].score, -50);
+        assert_eq!(games[0].aging_drawn, 2);
+        assert_eq!(
+            games[0].death.as_deref(),
+            Some("Exploring the island (Yellow)")
+        );
+        assert_eq!(games[1].score, -59);
+        assert_eq!(games[1].aging_drawn, 5);
+        assert_eq!(games[1].death.as_deref(), Some("Wild animals (Yellow)"));
+
+        // the same seed plays the same game
+        let again = play_game(&rules, 0, &cards, &mut HeuristicStrategy).unwrap();
+        assert_eq!(
+            serde_json::to_string(&again).unwrap(),
+            serde_json::to_string(&games[0]).unwrap()
+        );
+
+        // the highest seed is played too
+        play_game(&rules, u64::MAX, &cards, &mut HeuristicStrategy).unwrap();
+    }
+
+    #[test]
+    fn test_summarize() {
+        let rules = RuleSet::level(1).unwrap();
+        let cards = CardSet::builtin();
+        let games: Vec<_> = (0..2)
+            .map(|seed| play_game(&rules, seed, &cards, &mut HeuristicStrategy).unwrap())
+            .collect();
+        let summary = summarize(1, &games.iter().collect::<Vec<_>>());
+
+        assert_eq!(summary.games, 2);
+        assert_eq!(summary.wins, 0);
+        assert_eq!(summary.score.min, -59);
+        assert_eq!(summary.score.max, -50);
+        assert_eq!(summary.score.mean, -54.5);
+        assert_eq!(summary.score.median, -50);
+        assert_eq!(summary.score.histogram.values().sum::<usize>(), 2);
+        assert_eq!(summary.aging_drawn_per_game, 3.5);
+        assert_eq!(summary.deaths.values().sum::<usize>(), 2);
+
+        let empty = summarize(1, &[]);
+        assert_eq!(empty.games, 0);
+        assert_eq!(empty.win_rate, 0.0);
+    }
+}
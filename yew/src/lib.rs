@@ -8,8 +8,8 @@ use stdweb::web::Date;
 use yew::prelude::*;
 use yew::services::ConsoleService;
 
-pub struct Model<'a> {
-    friday: friday::Friday<'a>,
+pub struct Model {
+    friday: friday::Friday,
     console: ConsoleService,
     value: i64,
 }
@@ -20,14 +20,14 @@ pub enum Msg {
     Bulk(Vec<Msg>),
 }
 
-impl<'a> Component for Model<'a> {
+impl Component for Model {
     type Message = Msg;
     type Properties = ();
 
     fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
         Model {
             console: ConsoleService::new(),
-            friday: friday::Friday::new(1),
+            friday: friday::Friday::new(1).unwrap(),
             value: 0,
         }
     }
@@ -53,7 +53,7 @@ impl<'a> Component for Model<'a> {
     }
 }
 
-impl<'a> Renderable<Model<'a>> for Model<'a> {
+impl Renderable<Model> for Model {
     fn view(&self) -> Html<Self> {
         html! {
             <div>